@@ -0,0 +1,692 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Output, Stdio};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::app::IfaceState;
+use crate::config::glob_match;
+use crate::constants::HOOK_OUTPUT_LINES;
+use crate::events::{EventKind, EventLog};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Rx,
+    Tx,
+    Total,
+    RxPackets,
+    TxPackets,
+    Errors,
+    Drops,
+    Util,
+}
+
+impl Metric {
+    const ALL: [(&'static str, Metric); 8] = [
+        ("rx", Metric::Rx),
+        ("tx", Metric::Tx),
+        ("total", Metric::Total),
+        ("rx_packets", Metric::RxPackets),
+        ("tx_packets", Metric::TxPackets),
+        ("errors", Metric::Errors),
+        ("drops", Metric::Drops),
+        ("util", Metric::Util),
+    ];
+
+    fn parse(token: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|(name, _)| *name == token)
+            .map(|(_, metric)| *metric)
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(_, metric)| *metric == self)
+            .map(|(name, _)| *name)
+            .unwrap_or("?")
+    }
+
+    pub fn is_byte_rate(self) -> bool {
+        matches!(self, Metric::Rx | Metric::Tx | Metric::Total)
+    }
+
+    /// Current value for `iface`: bytes/s for the byte rates, per second for
+    /// packets, errors and drops, percent for utilization. `None` when the
+    /// metric doesn't apply, e.g. utilization without a known link speed.
+    pub fn value(self, iface: &IfaceState) -> Option<f64> {
+        match self {
+            Metric::Rx => Some(iface.rx_rate),
+            Metric::Tx => Some(iface.tx_rate),
+            Metric::Total => Some(iface.total_rate),
+            Metric::RxPackets => Some(iface.rx_packet_rate),
+            Metric::TxPackets => Some(iface.tx_packet_rate),
+            Metric::Errors => Some(iface.error_rate),
+            Metric::Drops => Some(iface.drop_rate),
+            Metric::Util => iface.utilization(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Above,
+    AtLeast,
+    Below,
+    AtMost,
+}
+
+impl Op {
+    fn parse(token: &str) -> Option<Self> {
+        match token {
+            ">" => Some(Op::Above),
+            ">=" => Some(Op::AtLeast),
+            "<" => Some(Op::Below),
+            "<=" => Some(Op::AtMost),
+            _ => None,
+        }
+    }
+
+    fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Op::Above => value > threshold,
+            Op::AtLeast => value >= threshold,
+            Op::Below => value < threshold,
+            Op::AtMost => value <= threshold,
+        }
+    }
+}
+
+/// A threshold on one metric, written the way you'd say it:
+///
+/// ```text
+/// [IFACE] METRIC OP VALUE[UNIT] [for DURATION] [run COMMAND]
+/// en0 rx > 50 MB/s for 10s
+/// any utun tx > 1 MB/s
+/// errors > 0
+/// en0 util > 90% for 30s run ~/bin/capture.sh
+/// ```
+///
+/// IFACE is a name or glob; `any` (or leaving it out) matches every
+/// interface and `any PREFIX` is shorthand for `PREFIX*`. Byte rates take
+/// the same units the UI shows (`B/s` ... `GB/s` in 1024 steps, `b/s` ...
+/// `Gb/s` in 1000 steps); `util` is a percentage of link speed. Everything
+/// after `run` is handed to `sh -c` when the alert fires.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlertRule {
+    pub iface: Option<String>,
+    pub metric: Metric,
+    pub op: Op,
+    /// In the metric's base unit (bytes/s for byte rates).
+    pub threshold: f64,
+    /// How long the condition must hold before the alert fires.
+    pub for_secs: f64,
+    /// Shell command to run when the alert fires.
+    pub hook: Option<String>,
+    /// The condition as written, without the hook.
+    text: String,
+}
+
+impl AlertRule {
+    pub fn matches(&self, name: &str) -> bool {
+        self.iface
+            .as_deref()
+            .map_or(true, |pattern| glob_match(pattern, name))
+    }
+}
+
+fn parse_threshold(metric: Metric, text: &str) -> Result<f64, String> {
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid threshold \"{text}\""))?;
+    let scale = if metric.is_byte_rate() {
+        match unit {
+            "" | "B/s" => 1.0,
+            "KB/s" => 1024.0,
+            "MB/s" => 1024.0 * 1024.0,
+            "GB/s" => 1024.0 * 1024.0 * 1024.0,
+            "b/s" => 1.0 / 8.0,
+            "Kb/s" => 1e3 / 8.0,
+            "Mb/s" => 1e6 / 8.0,
+            "Gb/s" => 1e9 / 8.0,
+            _ => return Err(format!("unknown rate unit \"{unit}\"")),
+        }
+    } else if metric == Metric::Util {
+        match unit {
+            "" | "%" => 1.0,
+            _ => return Err(format!("utilization is a percentage, not \"{unit}\"")),
+        }
+    } else {
+        match unit {
+            "" | "/s" => 1.0,
+            _ => {
+                return Err(format!(
+                    "{} is a count per second, not \"{unit}\"",
+                    metric.name()
+                ))
+            }
+        }
+    };
+    Ok(value * scale)
+}
+
+pub fn parse_duration(text: &str) -> Result<f64, String> {
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration \"{text}\""))?;
+    match unit {
+        "" | "s" => Ok(value),
+        "m" => Ok(value * 60.0),
+        "h" => Ok(value * 3600.0),
+        "d" => Ok(value * 86400.0),
+        _ => Err(format!("unknown duration unit \"{unit}\"")),
+    }
+}
+
+impl FromStr for AlertRule {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        // The command keeps its own spacing and quoting, so split it off the
+        // raw text before tokenizing the condition.
+        let (text, hook) = match text.split_once(" run ") {
+            Some((condition, command)) if command.trim().is_empty() => {
+                return Err(format!("no command after \"run\" in \"{condition}\""));
+            }
+            Some((condition, command)) => (condition, Some(command.trim().to_string())),
+            None => (text, None),
+        };
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        let (metric_at, metric) = tokens
+            .iter()
+            .enumerate()
+            .find_map(|(i, t)| Metric::parse(t).map(|metric| (i, metric)))
+            .ok_or_else(|| format!("no metric in \"{text}\""))?;
+        let iface = match &tokens[..metric_at] {
+            [] | ["any"] => None,
+            ["any", prefix] => Some(format!("{prefix}*")),
+            [pattern] => Some(pattern.to_string()),
+            _ => return Err(format!("unexpected \"{}\"", tokens[..metric_at].join(" "))),
+        };
+
+        let rest = &tokens[metric_at + 1..];
+        let op = rest
+            .first()
+            .and_then(|t| Op::parse(t))
+            .ok_or_else(|| format!("expected >, >=, < or <= after {}", metric.name()))?;
+        let for_at = rest.iter().position(|t| *t == "for").unwrap_or(rest.len());
+        let threshold = rest[1..for_at].concat();
+        if threshold.is_empty() {
+            return Err("missing threshold".to_string());
+        }
+        let threshold = parse_threshold(metric, &threshold)?;
+        let for_secs = match rest.get(for_at + 1..) {
+            None => 0.0,
+            Some([]) => return Err("expected a duration after \"for\"".to_string()),
+            Some(duration) => parse_duration(&duration.concat())?,
+        };
+
+        Ok(Self {
+            iface,
+            metric,
+            op,
+            threshold,
+            for_secs,
+            hook,
+            text: tokens.join(" "),
+        })
+    }
+}
+
+impl fmt::Display for AlertRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// One interface currently breaching one rule.
+#[derive(Clone, Debug)]
+pub struct Breach {
+    pub rule: usize,
+    pub iface: String,
+    pub value: f64,
+    /// Sampler clock when the condition started holding.
+    pub since_us: u64,
+    /// Held for the rule's full duration.
+    pub firing: bool,
+}
+
+type BreachKey = (usize, String);
+
+/// What a finished hook sends back from its waiter thread.
+struct HookResult {
+    key: BreachKey,
+    /// Which run of the hook this was, so output from one that was killed
+    /// for timing out isn't mistaken for a later run's.
+    run: u64,
+    output: io::Result<Output>,
+}
+
+/// A hook that hasn't finished yet.
+struct RunningHook {
+    run: u64,
+    /// Also the id of the hook's process group.
+    pid: u32,
+    started: Instant,
+}
+
+/// Runs alert hooks in the background. Each rule/interface pair gets at most
+/// one run in flight and one start per cooldown, so a flapping alert can't
+/// fork-bomb the machine. A run that outlives the timeout is killed.
+struct Hooks {
+    cooldown: Duration,
+    timeout: Duration,
+    last_start: HashMap<BreachKey, Instant>,
+    running: HashMap<BreachKey, RunningHook>,
+    runs: u64,
+    tx: Sender<HookResult>,
+    rx: Receiver<HookResult>,
+}
+
+impl Hooks {
+    fn new(cooldown: Duration, timeout: Duration) -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            cooldown,
+            timeout,
+            last_start: HashMap::new(),
+            running: HashMap::new(),
+            runs: 0,
+            tx,
+            rx,
+        }
+    }
+
+    fn start(&mut self, rule: &AlertRule, breach: &Breach, events: &mut EventLog) {
+        let Some(command) = &rule.hook else {
+            return;
+        };
+        let key = (breach.rule, breach.iface.clone());
+        let iface = Some(breach.iface.as_str());
+        if self.running.contains_key(&key) {
+            events.push(
+                EventKind::Hook,
+                iface,
+                "hook skipped: previous run still going",
+            );
+            return;
+        }
+        if let Some(last) = self.last_start.get(&key) {
+            if last.elapsed() < self.cooldown {
+                events.push(
+                    EventKind::Hook,
+                    iface,
+                    format!(
+                        "hook skipped: ran {}s ago, cooldown {}s",
+                        last.elapsed().as_secs(),
+                        self.cooldown.as_secs()
+                    ),
+                );
+                return;
+            }
+        }
+
+        let unit = if rule.metric.is_byte_rate() {
+            "bytes_per_second"
+        } else if rule.metric == Metric::Util {
+            "percent"
+        } else {
+            "per_second"
+        };
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .env("MACNETMON_IFACE", &breach.iface)
+            .env("MACNETMON_METRIC", rule.metric.name())
+            .env("MACNETMON_VALUE", format!("{:.3}", breach.value))
+            .env("MACNETMON_THRESHOLD", format!("{:.3}", rule.threshold))
+            .env("MACNETMON_UNIT", unit)
+            .env("MACNETMON_RULE", rule.to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            // Its own group, so a timeout takes down whatever it started too.
+            .process_group(0)
+            .spawn();
+        let child = match child {
+            Ok(child) => child,
+            Err(err) => {
+                events.push(
+                    EventKind::Error,
+                    iface,
+                    format!("hook failed to start: {err}"),
+                );
+                return;
+            }
+        };
+
+        events.push(EventKind::Hook, iface, format!("running hook: {command}"));
+        self.runs += 1;
+        let run = self.runs;
+        self.last_start.insert(key.clone(), Instant::now());
+        self.running.insert(
+            key.clone(),
+            RunningHook {
+                run,
+                pid: child.id(),
+                started: Instant::now(),
+            },
+        );
+        let tx = self.tx.clone();
+        thread::spawn(move || {
+            let output = child.wait_with_output();
+            let _ = tx.send(HookResult { key, run, output });
+        });
+    }
+
+    /// Kills hooks that have run past the timeout and moves output from
+    /// finished ones into the event log.
+    fn collect(&mut self, events: &mut EventLog) {
+        let timeout = self.timeout;
+        self.running.retain(|(_, iface), hook| {
+            if hook.started.elapsed() < timeout {
+                return true;
+            }
+            // The waiter thread reaps it once its pipes close.
+            unsafe { libc::kill(-(hook.pid as libc::pid_t), libc::SIGKILL) };
+            events.push(
+                EventKind::Error,
+                Some(iface),
+                format!("hook timed out after {}s", timeout.as_secs()),
+            );
+            false
+        });
+
+        while let Ok(HookResult { key, run, output }) = self.rx.try_recv() {
+            if self.running.get(&key).map(|hook| hook.run) != Some(run) {
+                continue;
+            }
+            self.running.remove(&key);
+            let iface = Some(key.1.as_str());
+            let output = match output {
+                Ok(output) => output,
+                Err(err) => {
+                    events.push(EventKind::Error, iface, format!("hook failed: {err}"));
+                    continue;
+                }
+            };
+            let text = String::from_utf8_lossy(&output.stdout).to_string()
+                + &String::from_utf8_lossy(&output.stderr);
+            let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+            for line in lines.iter().take(HOOK_OUTPUT_LINES) {
+                events.push(EventKind::Hook, iface, format!("hook: {line}"));
+            }
+            if lines.len() > HOOK_OUTPUT_LINES {
+                events.push(
+                    EventKind::Hook,
+                    iface,
+                    format!("hook: ({} more lines)", lines.len() - HOOK_OUTPUT_LINES),
+                );
+            }
+            if output.status.success() {
+                events.push(EventKind::Hook, iface, "hook finished");
+            } else {
+                events.push(EventKind::Error, iface, format!("hook {}", output.status));
+            }
+        }
+    }
+}
+
+/// Rules plus the breaches they currently have open.
+pub struct Alerts {
+    pub rules: Vec<AlertRule>,
+    breaches: HashMap<BreachKey, Breach>,
+    hooks: Hooks,
+}
+
+impl Default for Alerts {
+    fn default() -> Self {
+        Self::new(Vec::new(), Duration::ZERO, Duration::MAX)
+    }
+}
+
+impl Alerts {
+    pub fn new(rules: Vec<AlertRule>, hook_cooldown: Duration, hook_timeout: Duration) -> Self {
+        Self {
+            rules,
+            breaches: HashMap::new(),
+            hooks: Hooks::new(hook_cooldown, hook_timeout),
+        }
+    }
+
+    /// Checks every rule against every interface that is up. Returns the
+    /// breaches that started firing on this tick. Hooks only run when
+    /// `run_hooks` is set, so replaying a trace doesn't act on old traffic.
+    pub fn evaluate<'a>(
+        &mut self,
+        states: impl Iterator<Item = &'a IfaceState> + Clone,
+        now_us: u64,
+        run_hooks: bool,
+        events: &mut EventLog,
+    ) -> Vec<Breach> {
+        self.hooks.collect(events);
+        let mut fired = Vec::new();
+        for (idx, rule) in self.rules.iter().enumerate() {
+            for iface in states.clone().filter(|s| s.up && rule.matches(&s.name)) {
+                let key = (idx, iface.name.clone());
+                let holding = rule
+                    .metric
+                    .value(iface)
+                    .filter(|value| rule.op.holds(*value, rule.threshold));
+                let Some(value) = holding else {
+                    if let Some(breach) = self.breaches.remove(&key) {
+                        if breach.firing {
+                            events.push(
+                                EventKind::AlertCleared,
+                                Some(&iface.name),
+                                format!("alert cleared: {rule}"),
+                            );
+                        }
+                    }
+                    continue;
+                };
+                let breach = self.breaches.entry(key).or_insert_with(|| Breach {
+                    rule: idx,
+                    iface: iface.name.clone(),
+                    value,
+                    since_us: now_us,
+                    firing: false,
+                });
+                breach.value = value;
+                let held = now_us.saturating_sub(breach.since_us) as f64 / 1_000_000.0;
+                if !breach.firing && held >= rule.for_secs {
+                    breach.firing = true;
+                    events.push(
+                        EventKind::Alert,
+                        Some(&iface.name),
+                        format!("alert: {rule}"),
+                    );
+                    if run_hooks {
+                        self.hooks.start(rule, breach, events);
+                    }
+                    fired.push(breach.clone());
+                }
+            }
+        }
+        // Interfaces that went down or disappeared can't hold a breach.
+        self.breaches.retain(|_, breach| {
+            let up = states.clone().any(|s| s.up && s.name == breach.iface);
+            if !up && breach.firing {
+                events.push(
+                    EventKind::AlertCleared,
+                    Some(&breach.iface),
+                    format!("alert cleared: {}", self.rules[breach.rule]),
+                );
+            }
+            up
+        });
+        fired
+    }
+
+    /// Breaches that are firing, in rule order.
+    pub fn active(&self) -> Vec<&Breach> {
+        let mut active: Vec<&Breach> = self.breaches.values().filter(|b| b.firing).collect();
+        active.sort_by(|a, b| a.rule.cmp(&b.rule).then_with(|| a.iface.cmp(&b.iface)));
+        active
+    }
+
+    pub fn any_firing(&self) -> bool {
+        self.breaches.values().any(|b| b.firing)
+    }
+
+    pub fn is_alerting(&self, name: &str) -> bool {
+        self.breaches.values().any(|b| b.firing && b.iface == name)
+    }
+
+    pub fn clear(&mut self) {
+        self.breaches.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(text: &str) -> AlertRule {
+        text.parse().unwrap_or_else(|err| panic!("{text:?}: {err}"))
+    }
+
+    #[test]
+    fn interface_selectors() {
+        assert_eq!(rule("rx > 1").iface, None);
+        assert_eq!(rule("any rx > 1").iface, None);
+        assert_eq!(rule("any utun tx > 1").iface.as_deref(), Some("utun*"));
+        assert_eq!(rule("en* rx > 1").iface.as_deref(), Some("en*"));
+        assert!(rule("any utun tx > 1").matches("utun3"));
+        assert!(!rule("any utun tx > 1").matches("en0"));
+    }
+
+    #[test]
+    fn byte_and_bit_units() {
+        assert_eq!(rule("rx > 512").threshold, 512.0);
+        assert_eq!(rule("rx > 2 KB/s").threshold, 2048.0);
+        assert_eq!(rule("rx > 50MB/s").threshold, 50.0 * 1024.0 * 1024.0);
+        assert_eq!(rule("tx >= 1 GB/s").threshold, 1024.0 * 1024.0 * 1024.0);
+        assert_eq!(rule("rx > 8 b/s").threshold, 1.0);
+        assert_eq!(rule("rx > 100 Mb/s").threshold, 100e6 / 8.0);
+        assert_eq!(rule("total < 1 Gb/s").threshold, 1e9 / 8.0);
+        assert_eq!(rule("util > 90%").threshold, 90.0);
+        assert_eq!(rule("errors > 0.5/s").threshold, 0.5);
+    }
+
+    #[test]
+    fn durations_and_hooks() {
+        let parsed = rule("en0 util > 90% for 30s run ~/bin/capture.sh  --fast");
+        assert_eq!(parsed.metric, Metric::Util);
+        assert_eq!(parsed.op, Op::Above);
+        assert_eq!(parsed.for_secs, 30.0);
+        assert_eq!(parsed.hook.as_deref(), Some("~/bin/capture.sh  --fast"));
+        assert_eq!(parsed.to_string(), "en0 util > 90% for 30s");
+        assert_eq!(rule("rx > 1 for 2m").for_secs, 120.0);
+        assert_eq!(rule("rx > 1 for 1.5 h").for_secs, 5400.0);
+        assert_eq!(rule("rx > 1").for_secs, 0.0);
+    }
+
+    #[test]
+    fn rejects_malformed_rules() {
+        for text in [
+            "",
+            "en0 > 5",
+            "en0 rx 5",
+            "en0 rx =>",
+            "rx >",
+            "rx > fast",
+            "rx > 5 KiB/s",
+            "util > 5 MB/s",
+            "errors > 5 KB/s",
+            "rx > 5 for",
+            "rx > 5 for 3w",
+            "rx > 5 run  ",
+            "en0 en1 rx > 5",
+        ] {
+            assert!(text.parse::<AlertRule>().is_err(), "accepted {text:?}");
+        }
+    }
+
+    fn breach(iface: &str) -> Breach {
+        Breach {
+            rule: 0,
+            iface: iface.to_string(),
+            value: 2.0,
+            since_us: 0,
+            firing: true,
+        }
+    }
+
+    /// Collects until no hook is left running, or gives up after 10s.
+    fn wait(hooks: &mut Hooks, events: &mut EventLog) {
+        for _ in 0..1000 {
+            hooks.collect(events);
+            if hooks.running.is_empty() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("hook still running");
+    }
+
+    fn messages(events: &EventLog) -> Vec<(EventKind, &str)> {
+        let mut messages: Vec<_> = events
+            .iter()
+            .map(|event| (event.kind, event.message.as_str()))
+            .collect();
+        messages.reverse();
+        messages
+    }
+
+    #[test]
+    fn hooks_see_the_breach_and_log_their_output() {
+        let rule = rule("en0 rx > 1 run echo $MACNETMON_IFACE $MACNETMON_METRIC $MACNETMON_UNIT");
+        let mut hooks = Hooks::new(Duration::ZERO, Duration::from_secs(10));
+        let mut events = EventLog::default();
+        hooks.start(&rule, &breach("en0"), &mut events);
+        wait(&mut hooks, &mut events);
+        assert_eq!(
+            messages(&events),
+            [
+                (
+                    EventKind::Hook,
+                    "running hook: echo $MACNETMON_IFACE $MACNETMON_METRIC $MACNETMON_UNIT"
+                ),
+                (EventKind::Hook, "hook: en0 rx bytes_per_second"),
+                (EventKind::Hook, "hook finished"),
+            ]
+        );
+    }
+
+    #[test]
+    fn hooks_are_killed_after_the_timeout() {
+        let rule = rule("en0 rx > 1 run sleep 30");
+        let mut hooks = Hooks::new(Duration::ZERO, Duration::from_millis(100));
+        let mut events = EventLog::default();
+        hooks.start(&rule, &breach("en0"), &mut events);
+        wait(&mut hooks, &mut events);
+        assert_eq!(
+            messages(&events).last(),
+            Some(&(EventKind::Error, "hook timed out after 0s"))
+        );
+    }
+}
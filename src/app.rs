@@ -1,32 +1,82 @@
-use std::collections::{HashMap, HashSet};
-use std::time::Instant;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use ratatui::layout::Rect;
 use ratatui::style::{Color, Style};
 
+use crate::alert::{AlertRule, Alerts};
 use crate::args::Args;
-use crate::config::Config;
-use crate::constants::{HISTORY_LEN, INTERVAL_STEP_MS, MAX_INTERVAL_MS, MIN_INTERVAL_MS};
-use crate::model::{DisplaySettings, Group, SortMode};
+use crate::config::{glob_match, matches_any, Config};
+use crate::constants::{
+    HISTORY_LEN, INTERVAL_STEP_MS, MAX_INTERVAL_MS, MIN_INTERVAL_MS, SCROLLBACK_COLUMNS,
+};
+use crate::events::{EventKind, EventLog};
+use crate::history::{self, Ring};
+use crate::model::{
+    resample, DisplaySettings, FocusMove, Group, Point, Resample, SortMode, Window,
+};
 use crate::net::{
-    get_hostname, is_physical_interface, is_up, load_interface_aliases, sample_interfaces,
-    InterfaceSample,
+    get_hostname, is_running, load_interface_aliases, Family, IfaceAddress, InterfaceSample,
+    InterfaceSampler, Snapshot, SystemSampler,
 };
+use crate::prometheus::Exporter;
+use crate::rollup::{self, RollupFile, Rollups};
+use crate::store::{self, HistoryStore};
 use crate::theme::{build_themes, solid_tx_color, BorderColors, Theme, SOLID_THEMES};
+use crate::usage::{Quota, Usage};
 
 #[derive(Clone, Debug)]
 pub struct IfaceState {
     pub name: String,
     pub rx_bytes: u64,
     pub tx_bytes: u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_drops: u64,
+    pub tx_drops: u64,
+    pub collisions: u64,
+    pub rx_multicast: u64,
+    pub tx_multicast: u64,
     pub rx_rate: f64,
     pub tx_rate: f64,
     pub total_rate: f64,
+    /// Packets per second.
+    pub rx_packet_rate: f64,
+    pub tx_packet_rate: f64,
+    /// Receive plus transmit errors per second.
+    pub error_rate: f64,
+    /// Receive plus transmit drops per second.
+    pub drop_rate: f64,
+    pub collision_rate: f64,
+    /// Link speed in bits per second; 0 when unknown.
+    pub link_speed: u64,
+    pub mtu: u32,
+    pub mac: Option<String>,
+    pub addresses: Vec<IfaceAddress>,
     pub flags: u32,
     pub is_loopback: bool,
-    pub rx_history: Vec<u64>,
-    pub tx_history: Vec<u64>,
+    pub is_physical: bool,
+    /// Administratively up with a live link (`IFF_UP` and `IFF_RUNNING`).
+    /// Down interfaces are kept, with their history frozen until they return.
+    pub up: bool,
+    /// Up/down changes seen since the interface was first sampled.
+    pub transitions: u64,
+    /// Rate histories, newest first.
+    pub rx_history: History,
+    pub tx_history: History,
+    pub rx_packet_history: History,
+    pub tx_packet_history: History,
+    pub error_history: History,
+    /// Times the counters jumped backwards by more than a 32-bit wrap.
+    pub counter_resets: u64,
     pub last_active_sample: u64,
     pub initialized: bool,
+    /// Bytes moved since this interface was first seen.
+    pub session_rx_bytes: u64,
+    pub session_tx_bytes: u64,
 }
 
 impl IfaceState {
@@ -35,22 +85,95 @@ impl IfaceState {
             name: sample.name.clone(),
             rx_bytes: sample.rx_bytes,
             tx_bytes: sample.tx_bytes,
+            rx_packets: sample.rx_packets,
+            tx_packets: sample.tx_packets,
+            rx_errors: sample.rx_errors,
+            tx_errors: sample.tx_errors,
+            rx_drops: sample.rx_drops,
+            tx_drops: sample.tx_drops,
+            collisions: sample.collisions,
+            rx_multicast: sample.rx_multicast,
+            tx_multicast: sample.tx_multicast,
             rx_rate: 0.0,
             tx_rate: 0.0,
             total_rate: 0.0,
+            rx_packet_rate: 0.0,
+            tx_packet_rate: 0.0,
+            error_rate: 0.0,
+            drop_rate: 0.0,
+            collision_rate: 0.0,
+            link_speed: sample.link_speed,
+            mtu: sample.mtu,
+            mac: sample.mac.clone(),
+            addresses: sample.addresses.clone(),
             flags: sample.flags,
             is_loopback: sample.is_loopback,
-            rx_history: Vec::with_capacity(HISTORY_LEN),
-            tx_history: Vec::with_capacity(HISTORY_LEN),
+            is_physical: sample.is_physical,
+            up: is_running(sample.flags),
+            transitions: 0,
+            rx_history: History::with_capacity(HISTORY_LEN),
+            tx_history: History::with_capacity(HISTORY_LEN),
+            rx_packet_history: History::with_capacity(HISTORY_LEN),
+            tx_packet_history: History::with_capacity(HISTORY_LEN),
+            error_history: History::with_capacity(HISTORY_LEN),
+            counter_resets: 0,
             last_active_sample: 0,
             initialized: false,
+            session_rx_bytes: 0,
+            session_tx_bytes: 0,
+        }
+    }
+
+    /// Link capacity in bytes per second, if the link speed is known.
+    pub fn capacity(&self) -> Option<f64> {
+        (self.link_speed > 0).then(|| self.link_speed as f64 / 8.0)
+    }
+
+    /// Busier direction as a percentage of link capacity. Links are full
+    /// duplex, so RX and TX each get the whole speed rather than sharing it.
+    pub fn utilization(&self) -> Option<f64> {
+        self.capacity()
+            .map(|cap| self.rx_rate.max(self.tx_rate) / cap * 100.0)
+    }
+
+    /// The address most likely to identify the interface: the first IPv4
+    /// address, else the first IPv6 address that isn't link-local.
+    pub fn primary_address(&self) -> Option<&IfaceAddress> {
+        self.addresses
+            .iter()
+            .find(|a| a.family == Family::Inet)
+            .or_else(|| {
+                self.addresses.iter().find(|a| match a.address {
+                    IpAddr::V6(v6) => v6.segments()[0] & 0xffc0 != 0xfe80,
+                    IpAddr::V4(_) => false,
+                })
+            })
+    }
+
+    fn clear_rates(&mut self) {
+        self.rx_rate = 0.0;
+        self.tx_rate = 0.0;
+        self.total_rate = 0.0;
+        self.rx_packet_rate = 0.0;
+        self.tx_packet_rate = 0.0;
+        self.error_rate = 0.0;
+        self.drop_rate = 0.0;
+        self.collision_rate = 0.0;
+    }
+
+    pub fn group(&self) -> Group {
+        if self.is_physical {
+            Group::Physical
+        } else {
+            Group::Virtual
         }
     }
 }
 
 pub struct App {
     pub states: HashMap<String, IfaceState>,
-    pub last_sample: Instant,
+    pub sampler: Box<dyn InterfaceSampler>,
+    pub last_sample_us: u64,
     pub sample_index: u64,
     pub hostname: String,
     pub display: DisplaySettings,
@@ -65,16 +188,180 @@ pub struct App {
     pub visible_virtual: HashSet<String>,
     pub total_rx: f64,
     pub total_tx: f64,
-    pub total_rx_history: Vec<u64>,
-    pub total_tx_history: Vec<u64>,
+    pub total_rx_history: History,
+    pub total_tx_history: History,
+    pub exporter: Option<Exporter>,
+    /// Interface shown full-screen in the detail view, if open.
+    pub detail: Option<String>,
+    /// Tiles as they were last drawn, physical group first, with their screen
+    /// areas so focus can move between them spatially.
+    pub tiles: Vec<(String, Rect)>,
+    /// Tile under the keyboard cursor.
+    pub focus: Option<String>,
+    pub pinned: Vec<String>,
+    pub hidden: Vec<String>,
+    /// Session-only allow list from `--only`; empty means everything.
+    pub only: Vec<String>,
+    pub events: EventLog,
+    /// Rows scrolled back from the newest entry in the event pane.
+    pub event_scroll: usize,
+    pub alerts: Alerts,
+    /// Rules as written in the config, kept verbatim so saving doesn't drop
+    /// ones that failed to parse or mix in `--alert` rules.
+    pub alert_rules: Vec<String>,
+    pub alert_bell: bool,
+    pub hook_cooldown_secs: u64,
+    pub hook_timeout_secs: u64,
+    /// An alert fired since the frontend last rang the bell.
+    pub bell_pending: bool,
+    /// Persistent daily/monthly totals; `None` during a replay, whose
+    /// traffic shouldn't count.
+    pub usage: Option<Usage>,
+    /// Quota as written in the config.
+    pub usage_quota: Option<String>,
+    pub billing_day: u32,
+    /// Wall-clock time of the latest sample in Unix microseconds, or trace
+    /// time for traces recorded without one.
+    pub sample_time_us: u64,
+    /// Long-window rollups per interface name. Kept by name rather than in
+    /// `IfaceState` so they outlive an interface that comes and goes.
+    pub rollups: HashMap<String, Rollups>,
+    pub total_rollups: Rollups,
+    pub keep_rollups: bool,
+    rollup_file: Option<RollupFile>,
+    pub keep_history: bool,
+    pub history_retention_days: u64,
+    history_store: Option<HistoryStore>,
+    /// RX/TX history read back from the store, handed to each interface
+    /// when it's first sampled.
+    restored_history: HashMap<String, (History, History)>,
+    /// Set while the display is paused for scrolling back through history.
+    pub scrollback: Option<Scrollback>,
+    /// Time each raw sparkline column covers: the configured interval when
+    /// live, the spacing of the trace during a replay.
+    column_us: u64,
+}
+
+/// Rates newest first, each with the time it was measured over.
+pub type History = Ring<Point>;
+
+/// Newest-first RX and TX rates for the current window, with the time each
+/// point stands for.
+pub struct WindowSeries<'a> {
+    pub rx: Series<'a>,
+    pub tx: Series<'a>,
+    pub step_secs: f64,
+    /// Wall-clock time of the newest point, in Unix microseconds.
+    pub time_us: u64,
+}
+
+/// One direction of a `WindowSeries`. The 5m window reads the raw history in
+/// place; the longer windows merge rollup buckets up front. Cloning is cheap,
+/// so each use can walk the series afresh.
+#[derive(Clone)]
+pub enum Series<'a> {
+    Raw(Resample<history::Iter<'a, Point>>),
+    Rollup(std::vec::IntoIter<Option<u64>>),
+}
+
+impl Iterator for Series<'_> {
+    type Item = Option<u64>;
+
+    fn next(&mut self) -> Option<Option<u64>> {
+        match self {
+            Series::Raw(columns) => columns.next(),
+            Series::Rollup(columns) => columns.next(),
+        }
+    }
+}
+
+/// What the sparklines show while paused. Sampling carries on underneath, so
+/// the raw history is copied when pausing and read from here instead.
+pub struct Scrollback {
+    /// Points back from the newest one, where the cursor sits.
+    pub cursor: usize,
+    /// `sample_time_us` when paused.
+    pub time_us: u64,
+    ifaces: HashMap<String, (History, History)>,
+    total: (History, History),
+}
+
+/// Change in a monotonic counter between two samples. A 32-bit source wraps
+/// modulo 2^32, so a backwards step is taken as a wrap when the wrapped delta
+/// is under half the range; anything else means the counter was reset and
+/// there is no meaningful delta.
+fn counter_delta(new: u64, old: u64, wrap32: bool) -> Option<u64> {
+    if new >= old {
+        return Some(new - old);
+    }
+    if wrap32 && old <= u32::MAX as u64 {
+        let wrapped = new + (1u64 << 32) - old;
+        if wrapped < 1u64 << 31 {
+            return Some(wrapped);
+        }
+    }
+    None
 }
 
 impl App {
     pub fn new(args: &Args) -> Self {
-        let cfg = Config::load();
+        Self::with_sampler(args, Box::new(SystemSampler::new()))
+    }
+
+    pub fn with_sampler(args: &Args, sampler: Box<dyn InterfaceSampler>) -> Self {
+        let mut app = Self::from_config(args, Config::load(), sampler);
+        if app.sampler.replay().is_none() {
+            let quota = match app.usage_quota.as_deref().map(Quota::parse) {
+                Some(Ok(quota)) => Some(quota),
+                Some(Err(err)) => {
+                    app.events.push(
+                        EventKind::Error,
+                        None,
+                        format!("ignoring usage quota: {err}"),
+                    );
+                    None
+                }
+                None => None,
+            };
+            app.usage = Some(Usage::load(app.billing_day, quota));
+            // Saved history only refills the raw samples, so it keeps the
+            // rollups too for the longer windows.
+            if app.keep_rollups || app.keep_history {
+                match RollupFile::open(&mut app.rollups, &mut app.total_rollups) {
+                    Ok(file) => app.rollup_file = Some(file),
+                    Err(err) => app.events.push(
+                        EventKind::Error,
+                        None,
+                        format!("can't keep rollups on disk: {err}"),
+                    ),
+                }
+            }
+            if app.keep_history {
+                match HistoryStore::open(app.history_retention_days) {
+                    Ok(store) => {
+                        app.restore_history(&store);
+                        app.history_store = Some(store);
+                    }
+                    Err(err) => app.events.push(
+                        EventKind::Error,
+                        None,
+                        format!("can't keep history on disk: {err}"),
+                    ),
+                }
+            }
+        }
+        app.save_config();
+        app.refresh_aliases();
+        app
+    }
+
+    /// Builds the app from `cfg` and `args` alone, without touching anything
+    /// on disk or looking up interface names.
+    fn from_config(args: &Args, cfg: Config, sampler: Box<dyn InterfaceSampler>) -> Self {
         let mut app = Self {
             states: HashMap::new(),
-            last_sample: Instant::now(),
+            sampler,
+            last_sample_us: 0,
             sample_index: 0,
             hostname: get_hostname().unwrap_or_else(|| "unknown".to_string()),
             display: cfg.display,
@@ -89,9 +376,55 @@ impl App {
             visible_virtual: HashSet::new(),
             total_rx: 0.0,
             total_tx: 0.0,
-            total_rx_history: Vec::with_capacity(HISTORY_LEN),
-            total_tx_history: Vec::with_capacity(HISTORY_LEN),
+            total_rx_history: History::with_capacity(HISTORY_LEN),
+            total_tx_history: History::with_capacity(HISTORY_LEN),
+            exporter: None,
+            detail: None,
+            tiles: Vec::new(),
+            focus: None,
+            pinned: cfg.pinned,
+            hidden: cfg.hidden,
+            only: args.only.clone(),
+            events: EventLog::default(),
+            event_scroll: 0,
+            alerts: Alerts::default(),
+            alert_rules: cfg.alerts,
+            alert_bell: cfg.alert_bell || args.bell,
+            hook_cooldown_secs: cfg.hook_cooldown_secs,
+            hook_timeout_secs: cfg.hook_timeout_secs,
+            bell_pending: false,
+            usage: None,
+            usage_quota: cfg.usage_quota,
+            billing_day: cfg.billing_day,
+            sample_time_us: 0,
+            rollups: HashMap::new(),
+            total_rollups: Rollups::default(),
+            keep_rollups: cfg.keep_rollups,
+            rollup_file: None,
+            keep_history: cfg.keep_history,
+            history_retention_days: cfg.history_retention_days,
+            history_store: None,
+            restored_history: HashMap::new(),
+            scrollback: None,
+            column_us: 0,
         };
+        let mut rules = Vec::new();
+        for text in &app.alert_rules {
+            match text.parse::<AlertRule>() {
+                Ok(rule) => rules.push(rule),
+                Err(err) => app.events.push(
+                    EventKind::Error,
+                    None,
+                    format!("ignoring alert rule \"{text}\": {err}"),
+                ),
+            }
+        }
+        rules.extend(args.alerts.iter().cloned());
+        app.alerts = Alerts::new(
+            rules,
+            Duration::from_secs(app.hook_cooldown_secs),
+            Duration::from_secs(app.hook_timeout_secs.max(1)),
+        );
         if let Some(idx) = app.themes.iter().position(|t| t.name == cfg.theme) {
             app.theme_index = idx;
         }
@@ -107,109 +440,681 @@ impl App {
         if args.bits {
             app.display.show_bits = true;
         }
+        for pattern in &args.exclude {
+            if !app.hidden.contains(pattern) {
+                app.hidden.push(pattern.clone());
+            }
+        }
         if let Some(interval) = args.interval {
             app.interval_ms = interval;
         }
         app.interval_ms = app.interval_ms.clamp(MIN_INTERVAL_MS, MAX_INTERVAL_MS);
-        app.save_config();
-        app.refresh_aliases();
         app
     }
 
     pub fn update(&mut self) {
-        let now = Instant::now();
-        let dt = now
-            .duration_since(self.last_sample)
-            .as_secs_f64()
-            .max(0.001);
-        self.last_sample = now;
         self.sample_index = self.sample_index.saturating_add(1);
 
-        match sample_interfaces() {
-            Ok(samples) => {
+        match self.sampler.sample() {
+            Ok(snapshot) => {
                 self.last_error = None;
-                let mut seen = HashSet::new();
+                self.apply_snapshot(snapshot);
+                // A replay is old traffic: show its alerts, but don't act on them.
+                let live = self.sampler.replay().is_none();
+                let fired = self.alerts.evaluate(
+                    self.states.values(),
+                    self.last_sample_us,
+                    live,
+                    &mut self.events,
+                );
+                if live && !fired.is_empty() && self.alert_bell {
+                    self.bell_pending = true;
+                }
+            }
+            Err(err) => {
+                let message = err.to_string();
+                if self.last_error.as_deref() != Some(message.as_str()) {
+                    self.events.push(
+                        EventKind::Error,
+                        None,
+                        format!("sampling failed: {message}"),
+                    );
+                }
+                self.last_error = Some(message);
+            }
+        }
 
-                for sample in samples {
-                    if !is_up(sample.flags) {
-                        continue;
-                    }
+        self.column_us = match self.sampler.replay() {
+            // Rounded to whole milliseconds so jitter doesn't shift columns.
+            Some(_) => match self.total_rx_history.newest() {
+                Some(point) => (point.dt_us + 500) / 1000 * 1000,
+                None => self.interval_ms * 1000,
+            },
+            None => self.interval_ms * 1000,
+        };
 
-                    seen.insert(sample.name.clone());
-
-                    let entry = self
-                        .states
-                        .entry(sample.name.clone())
-                        .or_insert_with(|| IfaceState::new(&sample));
-
-                    if entry.initialized {
-                        let rx_delta = sample.rx_bytes.saturating_sub(entry.rx_bytes);
-                        let tx_delta = sample.tx_bytes.saturating_sub(entry.tx_bytes);
-                        entry.rx_rate = rx_delta as f64 / dt;
-                        entry.tx_rate = tx_delta as f64 / dt;
-                        entry.total_rate = entry.rx_rate + entry.tx_rate;
-                    } else {
-                        entry.rx_rate = 0.0;
-                        entry.tx_rate = 0.0;
-                        entry.total_rate = 0.0;
-                    }
+        if let Some(exporter) = &self.exporter {
+            exporter.publish(self);
+        }
+    }
 
-                    entry.rx_bytes = sample.rx_bytes;
-                    entry.tx_bytes = sample.tx_bytes;
-                    entry.flags = sample.flags;
-                    entry.is_loopback = sample.is_loopback;
-                    entry.initialized = true;
-
-                    if entry.total_rate >= 1.0 {
-                        entry.last_active_sample = self.sample_index;
-                        if is_physical_interface(&entry.name) {
-                            self.visible_physical.insert(entry.name.clone());
-                        } else {
-                            self.visible_virtual.insert(entry.name.clone());
-                        }
+    fn apply_snapshot(&mut self, snapshot: Snapshot) {
+        let dt = (snapshot.elapsed_us.saturating_sub(self.last_sample_us) as f64 / 1_000_000.0)
+            .max(0.001);
+        self.last_sample_us = snapshot.elapsed_us;
+        self.sample_time_us = match snapshot.time_us {
+            0 => snapshot.elapsed_us,
+            time_us => time_us,
+        };
+        let secs = self.sample_time_us / 1_000_000;
+        let mut seen = HashSet::new();
+        // Everything is new on the first snapshot; only later arrivals are news.
+        let baseline = self.states.is_empty();
+        let mut usage_deltas = Vec::new();
+        let mut tick_rates = BTreeMap::new();
+
+        for sample in snapshot.interfaces {
+            seen.insert(sample.name.clone());
+
+            let is_new = !self.states.contains_key(&sample.name);
+            if !baseline && is_new {
+                self.events
+                    .push(EventKind::Appeared, Some(&sample.name), "appeared");
+            }
+            let entry = self
+                .states
+                .entry(sample.name.clone())
+                .or_insert_with(|| IfaceState::new(&sample));
+            if is_new {
+                if let Some((rx, tx)) = self.restored_history.remove(&entry.name) {
+                    entry.rx_history = rx;
+                    entry.tx_history = tx;
+                }
+            }
+
+            let fresh = !entry.initialized;
+            let mut reset = false;
+            if entry.initialized {
+                let mut deltas = [0u64; 9];
+                let counters = [
+                    (sample.rx_bytes, entry.rx_bytes),
+                    (sample.tx_bytes, entry.tx_bytes),
+                    (sample.rx_packets, entry.rx_packets),
+                    (sample.tx_packets, entry.tx_packets),
+                    (sample.rx_errors, entry.rx_errors),
+                    (sample.tx_errors, entry.tx_errors),
+                    (sample.rx_drops, entry.rx_drops),
+                    (sample.tx_drops, entry.tx_drops),
+                    (sample.collisions, entry.collisions),
+                ];
+                for (delta, (new, old)) in deltas.iter_mut().zip(counters) {
+                    match counter_delta(new, old, sample.wrap32) {
+                        Some(d) => *delta = d,
+                        None => reset = true,
                     }
+                }
+                if reset {
+                    self.events
+                        .push(EventKind::CounterReset, Some(&entry.name), "counters reset");
+                    entry.counter_resets += 1;
+                    deltas = [0; 9];
+                }
+                let [rx_delta, tx_delta, rx_packets, tx_packets, rx_errors, tx_errors, rx_drops, tx_drops, collisions] =
+                    deltas;
+                let rate = |delta: u64| delta as f64 / dt;
+                if self.usage.is_some() {
+                    usage_deltas.push((entry.name.clone(), rx_delta, tx_delta));
+                }
+
+                entry.rx_rate = rate(rx_delta);
+                entry.tx_rate = rate(tx_delta);
+                entry.session_rx_bytes = entry.session_rx_bytes.saturating_add(rx_delta);
+                entry.session_tx_bytes = entry.session_tx_bytes.saturating_add(tx_delta);
+                entry.total_rate = entry.rx_rate + entry.tx_rate;
+                entry.rx_packet_rate = rate(rx_packets);
+                entry.tx_packet_rate = rate(tx_packets);
+                entry.error_rate = rate(rx_errors) + rate(tx_errors);
+                entry.drop_rate = rate(rx_drops) + rate(tx_drops);
+                entry.collision_rate = rate(collisions);
+            } else {
+                entry.clear_rates();
+            }
+
+            let up = is_running(sample.flags);
+            if entry.initialized && up != entry.up {
+                let (kind, message) = if up {
+                    (EventKind::Up, "came up")
+                } else {
+                    (EventKind::Down, "went down")
+                };
+                self.events.push(kind, Some(&entry.name), message);
+                entry.transitions += 1;
+                // Give a link that comes back a full window before it can idle
+                // out of view again.
+                if up {
+                    entry.last_active_sample = self.sample_index;
+                }
+            }
+            entry.up = up;
+            if !up {
+                entry.clear_rates();
+            }
+
+            entry.rx_bytes = sample.rx_bytes;
+            entry.tx_bytes = sample.tx_bytes;
+            entry.rx_packets = sample.rx_packets;
+            entry.tx_packets = sample.tx_packets;
+            entry.rx_errors = sample.rx_errors;
+            entry.tx_errors = sample.tx_errors;
+            entry.rx_drops = sample.rx_drops;
+            entry.tx_drops = sample.tx_drops;
+            entry.collisions = sample.collisions;
+            entry.rx_multicast = sample.rx_multicast;
+            entry.tx_multicast = sample.tx_multicast;
+            entry.link_speed = sample.link_speed;
+            entry.mtu = sample.mtu;
+            entry.mac = sample.mac;
+            if entry.initialized && entry.addresses != sample.addresses {
+                let message = if sample.addresses.is_empty() {
+                    "lost all addresses".to_string()
+                } else {
+                    let list = sample
+                        .addresses
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>();
+                    format!("addresses now {}", list.join(", "))
+                };
+                self.events
+                    .push(EventKind::AddressChanged, Some(&entry.name), message);
+            }
+            entry.addresses = sample.addresses;
+            entry.flags = sample.flags;
+            entry.is_loopback = sample.is_loopback;
+            entry.is_physical = sample.is_physical;
+            entry.initialized = true;
+
+            // Counters keep tracking while the link is down so the first rate
+            // after it comes back is right, but history only resumes then.
+            if !entry.up {
+                continue;
+            }
 
-                    entry
-                        .rx_history
-                        .insert(0, entry.rx_rate.round().max(0.0) as u64);
-                    entry.rx_history.truncate(HISTORY_LEN);
-                    entry
-                        .tx_history
-                        .insert(0, entry.tx_rate.round().max(0.0) as u64);
-                    entry.tx_history.truncate(HISTORY_LEN);
+            if entry.total_rate >= 1.0 {
+                entry.last_active_sample = self.sample_index;
+                if entry.is_physical {
+                    self.visible_physical.insert(entry.name.clone());
+                } else {
+                    self.visible_virtual.insert(entry.name.clone());
                 }
+            }
 
-                self.states.retain(|name, _| seen.contains(name));
-                self.visible_physical
-                    .retain(|name| self.states.contains_key(name));
-                self.visible_virtual
-                    .retain(|name| self.states.contains_key(name));
-
-                let mut total_rx = 0.0;
-                let mut total_tx = 0.0;
-                for iface in self.states.values() {
-                    if !self.display.show_loopback && iface.is_loopback {
-                        continue;
+            let time_us = self.sample_time_us;
+            let dt_us = (dt * 1_000_000.0) as u64;
+            let point = |rate: f64| (!reset).then(|| rate.round().max(0.0) as u64);
+            let at = |value: Option<u64>| Point {
+                time_us,
+                dt_us,
+                value,
+            };
+            entry.rx_history.push(at(point(entry.rx_rate)));
+            entry.tx_history.push(at(point(entry.tx_rate)));
+            entry
+                .rx_packet_history
+                .push(at(point(entry.rx_packet_rate)));
+            entry
+                .tx_packet_history
+                .push(at(point(entry.tx_packet_rate)));
+            // Rounded up so a single error still registers.
+            entry
+                .error_history
+                .push(at((!reset).then(|| entry.error_rate.ceil().max(0.0) as u64)));
+
+            if !fresh {
+                tick_rates.insert(
+                    entry.name.clone(),
+                    (point(entry.rx_rate), point(entry.tx_rate)),
+                );
+            }
+            if !fresh && !reset {
+                let rollups = self.rollups.entry(entry.name.clone()).or_default();
+                let closed = rollups.add(
+                    secs,
+                    point(entry.rx_rate).unwrap_or(0),
+                    point(entry.tx_rate).unwrap_or(0),
+                );
+                if let Some(file) = &mut self.rollup_file {
+                    for (width, bucket) in closed {
+                        let _ = file.append(Some(&entry.name), width, &bucket);
                     }
-                    total_rx += iface.rx_rate;
-                    total_tx += iface.tx_rate;
                 }
+            }
+        }
+
+        for name in self.states.keys().filter(|name| !seen.contains(*name)) {
+            self.events
+                .push(EventKind::Disappeared, Some(name), "disappeared");
+        }
+        self.states.retain(|name, _| seen.contains(name));
+        if let Some(usage) = &mut self.usage {
+            if usage.record(&usage_deltas, &mut self.events) && self.alert_bell {
+                self.bell_pending = true;
+            }
+        }
+        self.visible_physical
+            .retain(|name| self.states.contains_key(name));
+        self.visible_virtual
+            .retain(|name| self.states.contains_key(name));
 
-                self.total_rx = total_rx;
-                self.total_tx = total_tx;
-                self.total_rx_history
-                    .insert(0, total_rx.round().max(0.0) as u64);
-                self.total_rx_history.truncate(HISTORY_LEN);
-                self.total_tx_history
-                    .insert(0, total_tx.round().max(0.0) as u64);
-                self.total_tx_history.truncate(HISTORY_LEN);
+        let mut total_rx = 0.0;
+        let mut total_tx = 0.0;
+        for iface in self.states.values() {
+            if !self.display.show_loopback && iface.is_loopback {
+                continue;
             }
+            total_rx += iface.rx_rate;
+            total_tx += iface.tx_rate;
+        }
+
+        self.total_rx = total_rx;
+        self.total_tx = total_tx;
+        let at = |rate: f64| Point {
+            time_us: self.sample_time_us,
+            dt_us: (dt * 1_000_000.0) as u64,
+            value: Some(rate.round().max(0.0) as u64),
+        };
+        self.total_rx_history.push(at(total_rx));
+        self.total_tx_history.push(at(total_tx));
+
+        if !baseline {
+            if let Some(store) = &mut self.history_store {
+                let total = (
+                    total_rx.round().max(0.0) as u64,
+                    total_tx.round().max(0.0) as u64,
+                );
+                let dt_us = (dt * 1_000_000.0) as u64;
+                if let Err(err) = store.append(self.sample_time_us, dt_us, total, &tick_rates) {
+                    self.events.push(
+                        EventKind::Error,
+                        None,
+                        format!("writing history failed: {err}"),
+                    );
+                    self.history_store = None;
+                }
+            }
+            let closed = self.total_rollups.add(
+                secs,
+                total_rx.round().max(0.0) as u64,
+                total_tx.round().max(0.0) as u64,
+            );
+            let hour_closed = closed.iter().any(|(width, _)| *width == 60 * 60);
+            if let Some(file) = &mut self.rollup_file {
+                for (width, bucket) in closed {
+                    let _ = file.append(None, width, &bucket);
+                }
+            }
+            if hour_closed {
+                rollup::prune(&mut self.rollups, secs);
+            }
+        }
+    }
+
+    /// Refills the raw history from the newest ticks in the on-disk store,
+    /// reading no further back than the history holds. The longer windows
+    /// come from the rollup file instead. Points keep their original times,
+    /// so the stretch macnetmon wasn't running shows up as a gap.
+    fn restore_history(&mut self, store: &HistoryStore) {
+        let now_us = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .unwrap_or(0);
+        let Some(dir) = HistoryStore::dir() else {
+            return;
+        };
+        let newest = match store::read_newest(&dir, store.cutoff_us(now_us), HISTORY_LEN) {
+            Ok(ticks) => ticks,
             Err(err) => {
-                self.last_error = Some(err.to_string());
+                self.events.push(
+                    EventKind::Error,
+                    None,
+                    format!("reading history failed: {err}"),
+                );
+                return;
             }
+        };
+
+        // Oldest first, so each push lands as the newest entry.
+        for (names, tick) in newest.iter().rev() {
+            let at = |value: Option<u64>| Point {
+                time_us: tick.t,
+                dt_us: tick.dt,
+                value,
+            };
+            for (name, (rx, tx)) in tick.ifaces(names) {
+                let (rx_history, tx_history) = self
+                    .restored_history
+                    .entry(name.to_string())
+                    .or_insert_with(|| {
+                        (
+                            History::with_capacity(HISTORY_LEN),
+                            History::with_capacity(HISTORY_LEN),
+                        )
+                    });
+                rx_history.push(at(rx));
+                tx_history.push(at(tx));
+            }
+            self.total_rx_history.push(at(Some(tick.total.0)));
+            self.total_tx_history.push(at(Some(tick.total.1)));
         }
     }
 
+    /// RX and TX history for `name` (or the all-interfaces total) over the
+    /// current window, merged down to at most `columns` points. The short
+    /// window reads the raw samples; longer ones read the minute rollups.
+    /// While paused, the short window holds every raw sample so it can be
+    /// scrolled, and the longer ones stop at the moment of pausing.
+    pub fn window_series(&self, name: Option<&str>) -> WindowSeries<'_> {
+        let window = self.display.window;
+        let time_us = match &self.scrollback {
+            Some(scrollback) => scrollback.time_us,
+            None => self.sample_time_us,
+        };
+        if window == Window::FiveMinutes {
+            let step_us = self.column_us.max(1000);
+            let history = match (&self.scrollback, name) {
+                (Some(scrollback), Some(name)) => {
+                    scrollback.ifaces.get(name).map(|(rx, tx)| (rx, tx))
+                }
+                (Some(scrollback), None) => Some((&scrollback.total.0, &scrollback.total.1)),
+                (None, Some(name)) => self
+                    .states
+                    .get(name)
+                    .map(|iface| (&iface.rx_history, &iface.tx_history)),
+                (None, None) => Some((&self.total_rx_history, &self.total_tx_history)),
+            };
+            let Some((rx, tx)) = history else {
+                return WindowSeries {
+                    rx: Series::Rollup(Vec::new().into_iter()),
+                    tx: Series::Rollup(Vec::new().into_iter()),
+                    step_secs: step_us as f64 / 1_000_000.0,
+                    time_us,
+                };
+            };
+            let len = match &self.scrollback {
+                // Reach back to the oldest sample so all of it can be scrolled.
+                Some(_) => {
+                    let oldest = rx
+                        .oldest()
+                        .map_or(time_us, |p| p.time_us - p.dt_us.min(p.time_us));
+                    let span = time_us.saturating_sub(oldest);
+                    ((span + step_us - 1) / step_us.max(1)) as usize
+                }
+                None => (window.secs() * 1_000_000 / step_us.max(1)) as usize,
+            }
+            .min(SCROLLBACK_COLUMNS);
+            return WindowSeries {
+                rx: Series::Raw(resample(rx, time_us, step_us, len)),
+                tx: Series::Raw(resample(tx, time_us, step_us, len)),
+                step_secs: step_us as f64 / 1_000_000.0,
+                time_us,
+            };
+        }
+
+        let rollups = match name {
+            Some(name) => self.rollups.get(name),
+            None => Some(&self.total_rollups),
+        };
+        let column_secs = window.rollup_column_secs();
+        let (rx, tx) = match rollups {
+            Some(rollups) => rollups
+                .minute
+                .series(time_us / 1_000_000, window.secs(), column_secs),
+            None => (Vec::new(), Vec::new()),
+        };
+        WindowSeries {
+            rx: Series::Rollup(rx.into_iter()),
+            tx: Series::Rollup(tx.into_iter()),
+            step_secs: column_secs as f64,
+            time_us,
+        }
+    }
+
+    /// Freezes the sparklines, or lets them run again. A replay stops
+    /// playing while paused.
+    pub fn toggle_pause(&mut self) {
+        self.scrollback = match self.scrollback {
+            Some(_) => None,
+            None => Some(self.freeze()),
+        };
+        let paused = self.scrollback.is_some();
+        if let Some(replay) = self.sampler.replay() {
+            replay.paused = paused;
+        }
+    }
+
+    fn freeze(&self) -> Scrollback {
+        Scrollback {
+            cursor: 0,
+            time_us: self.sample_time_us,
+            ifaces: self
+                .states
+                .values()
+                .map(|iface| {
+                    let history = (iface.rx_history.clone(), iface.tx_history.clone());
+                    (iface.name.clone(), history)
+                })
+                .collect(),
+            total: (self.total_rx_history.clone(), self.total_tx_history.clone()),
+        }
+    }
+
+    /// Moves the scrollback cursor `delta` points back in time (forward when
+    /// negative), stopping at the oldest point the window holds.
+    pub fn scroll_history(&mut self, delta: isize) {
+        let Some(scrollback) = &self.scrollback else {
+            return;
+        };
+        // As far back as the longest series reaches, so the cursor never
+        // points past the data.
+        let len = scrollback
+            .ifaces
+            .keys()
+            .map(|name| self.window_series(Some(name)).rx.count())
+            .chain([self.window_series(None).rx.count()])
+            .max()
+            .unwrap_or(0);
+        let Some(scrollback) = &mut self.scrollback else {
+            return;
+        };
+        let cursor = scrollback.cursor as isize + delta;
+        scrollback.cursor = cursor.clamp(0, len.saturating_sub(1) as isize) as usize;
+    }
+
+    /// How long to wait before the next `update`. Live samplers tick on the
+    /// configured interval; a replay follows the spacing of its trace and
+    /// returns `None` while paused or finished.
+    pub fn next_tick(&mut self) -> Option<Duration> {
+        match self.sampler.replay() {
+            Some(replay) => replay.next_delay(),
+            None => Some(Duration::from_millis(self.interval_ms)),
+        }
+    }
+
+    /// Moves a replay `delta_secs` forward or back. History is rebuilt by
+    /// feeding the trace again from just far enough back to fill it.
+    pub fn seek_replay(&mut self, delta_secs: f64) {
+        let Some(replay) = self.sampler.replay() else {
+            return;
+        };
+        let target = replay.index_at(delta_secs);
+        let start = target.saturating_sub(HISTORY_LEN);
+        replay.rewind_to(start);
+
+        // Rebuilding state re-detects the same changes; keep the log as it was.
+        let events = std::mem::take(&mut self.events);
+        self.alerts.clear();
+        self.states.clear();
+        self.visible_physical.clear();
+        self.visible_virtual.clear();
+        self.total_rx_history.clear();
+        self.total_tx_history.clear();
+        self.rollups.clear();
+        self.total_rollups = Rollups::default();
+        self.sample_index = 0;
+        self.last_sample_us = 0;
+
+        for _ in start..=target {
+            self.update();
+        }
+        self.events = events;
+        self.bell_pending = false;
+        if self.scrollback.is_some() {
+            self.scrollback = Some(self.freeze());
+        }
+    }
+
+    /// Opens the detail view on the focused tile, or the first one drawn.
+    pub fn open_detail(&mut self) {
+        let focused = self
+            .focus
+            .as_ref()
+            .filter(|name| self.tiles.iter().any(|(tile, _)| tile == *name));
+        self.detail = focused
+            .cloned()
+            .or_else(|| self.tiles.first().map(|(name, _)| name.clone()));
+    }
+
+    pub fn close_detail(&mut self) {
+        self.detail = None;
+    }
+
+    /// Steps the detail view to the next (or previous) tile in draw order.
+    pub fn cycle_detail(&mut self, forward: bool) {
+        let Some(current) = &self.detail else {
+            return;
+        };
+        let len = self.tiles.len();
+        if len == 0 {
+            return;
+        }
+        let idx = self
+            .tiles
+            .iter()
+            .position(|(name, _)| name == current)
+            .unwrap_or(0);
+        let next = if forward {
+            (idx + 1) % len
+        } else {
+            (idx + len - 1) % len
+        };
+        let name = self.tiles[next].0.clone();
+        self.focus = Some(name.clone());
+        self.detail = Some(name);
+    }
+
+    /// Moves the focus cursor to the nearest tile in `dir`, preferring tiles
+    /// that line up with the current one. With nothing focused yet, the first
+    /// tile drawn takes focus. In the detail view it pages between tiles.
+    pub fn move_focus(&mut self, dir: FocusMove) {
+        if self.detail.is_some() {
+            self.cycle_detail(matches!(dir, FocusMove::Right | FocusMove::Down));
+            return;
+        }
+
+        let current = self
+            .focus
+            .as_ref()
+            .and_then(|name| self.tiles.iter().find(|(tile, _)| tile == name));
+        let Some((current_name, current_rect)) = current else {
+            self.focus = self.tiles.first().map(|(name, _)| name.clone());
+            return;
+        };
+
+        // Doubled centres keep the arithmetic in integers.
+        let center = |r: &Rect| {
+            (
+                2 * r.x as i32 + r.width as i32,
+                2 * r.y as i32 + r.height as i32,
+            )
+        };
+        let (cx, cy) = center(current_rect);
+        let next = self
+            .tiles
+            .iter()
+            .filter(|(name, _)| name != current_name)
+            // Only tiles wholly past the current one's edge, so moving
+            // sideways off the end of a row doesn't drop into the next group.
+            .filter(|(_, rect)| match dir {
+                FocusMove::Left => rect.right() <= current_rect.x,
+                FocusMove::Right => rect.x >= current_rect.right(),
+                FocusMove::Up => rect.bottom() <= current_rect.y,
+                FocusMove::Down => rect.y >= current_rect.bottom(),
+            })
+            .filter_map(|(name, rect)| {
+                let (x, y) = center(rect);
+                let (along, across) = match dir {
+                    FocusMove::Left => (cx - x, (y - cy).abs()),
+                    FocusMove::Right => (x - cx, (y - cy).abs()),
+                    FocusMove::Up => (cy - y, (x - cx).abs()),
+                    FocusMove::Down => (y - cy, (x - cx).abs()),
+                };
+                (along > 0).then_some((along + 2 * across, name))
+            })
+            .min_by_key(|(score, _)| *score)
+            .map(|(_, name)| name.clone());
+
+        if next.is_some() {
+            self.focus = next;
+        }
+    }
+
+    pub fn clear_focus(&mut self) {
+        self.focus = None;
+    }
+
+    pub fn is_pinned(&self, name: &str) -> bool {
+        matches_any(&self.pinned, name)
+    }
+
+    /// Whether `name` is left out, either by `--only` or by a hide pattern
+    /// that no pin overrides.
+    pub fn is_hidden(&self, name: &str) -> bool {
+        (matches_any(&self.hidden, name) && !self.is_pinned(name))
+            || (!self.only.is_empty() && !matches_any(&self.only, name))
+    }
+
+    /// Pins the focused interface, or unpins it by dropping every pattern
+    /// that matches it.
+    pub fn toggle_pin(&mut self) {
+        let Some(name) = self.focus.clone() else {
+            return;
+        };
+        if self.is_pinned(&name) {
+            self.pinned.retain(|pattern| !glob_match(pattern, &name));
+        } else {
+            self.pinned.push(name);
+        }
+        self.save_config();
+    }
+
+    /// Hides the focused interface, unpinning it so the pin doesn't keep it
+    /// on screen, or unhides it by dropping every pattern that matches it.
+    pub fn toggle_hide(&mut self) {
+        let Some(name) = self.focus.clone() else {
+            return;
+        };
+        if matches_any(&self.hidden, &name) {
+            self.hidden.retain(|pattern| !glob_match(pattern, &name));
+        } else {
+            self.pinned.retain(|pattern| !glob_match(pattern, &name));
+            self.hidden.push(name);
+            if !self.display.show_hidden {
+                self.focus = None;
+            }
+        }
+        self.save_config();
+    }
+
     pub fn theme(&self) -> &Theme {
         &self.themes[self.theme_index % self.themes.len()]
     }
@@ -236,6 +1141,17 @@ impl App {
             display: self.display,
             sort_mode: self.sort_mode,
             interval_ms: self.interval_ms,
+            pinned: self.pinned.clone(),
+            hidden: self.hidden.clone(),
+            alerts: self.alert_rules.clone(),
+            alert_bell: self.alert_bell,
+            hook_cooldown_secs: self.hook_cooldown_secs,
+            hook_timeout_secs: self.hook_timeout_secs,
+            usage_quota: self.usage_quota.clone(),
+            billing_day: self.billing_day,
+            keep_rollups: self.keep_rollups,
+            keep_history: self.keep_history,
+            history_retention_days: self.history_retention_days,
         };
         cfg.save();
     }
@@ -255,6 +1171,7 @@ impl App {
             outer: t.outer,
             pane: t.pane,
             tile: t.graph,
+            alert: t.alert,
         }
     }
 
@@ -309,23 +1226,327 @@ impl App {
 
     pub fn in_group(&self, iface: &IfaceState, group: Group) -> bool {
         match group {
-            Group::Physical if !is_physical_interface(&iface.name) => return false,
-            Group::Virtual if is_physical_interface(&iface.name) => return false,
+            Group::Physical if !iface.is_physical => return false,
+            Group::Virtual if iface.is_physical => return false,
             _ => {}
         }
 
         true
     }
 
+    /// Scrolls the event pane by `delta` rows; positive goes further back.
+    pub fn scroll_events(&mut self, delta: isize) {
+        let max = self.events.len().saturating_sub(1) as isize;
+        self.event_scroll = (self.event_scroll as isize + delta).clamp(0, max) as usize;
+    }
+
     pub fn refresh_aliases(&mut self) {
         match load_interface_aliases() {
             Ok(map) => {
+                // The initial load at startup isn't a change worth logging.
+                if self.sample_index > 0 {
+                    self.log_alias_changes(&map);
+                }
                 self.aliases = map;
                 self.name_error = None;
             }
             Err(err) => {
-                self.name_error = Some(err.to_string());
+                let message = err.to_string();
+                self.events.push(
+                    EventKind::Error,
+                    None,
+                    format!("loading names failed: {message}"),
+                );
+                self.name_error = Some(message);
             }
         }
     }
+
+    fn log_alias_changes(&mut self, map: &HashMap<String, String>) {
+        let mut names: Vec<&String> = map.keys().chain(self.aliases.keys()).collect();
+        names.sort();
+        names.dedup();
+        for name in names {
+            let message = match (self.aliases.get(name), map.get(name)) {
+                (old, Some(new)) if old != Some(new) => format!("alias set to \"{new}\""),
+                (Some(_), None) => "alias removed".to_string(),
+                _ => continue,
+            };
+            self.events
+                .push(EventKind::AliasChanged, Some(name), message);
+        }
+    }
+}
+
+impl Drop for App {
+    fn drop(&mut self) {
+        if let Some(file) = &mut self.rollup_file {
+            file.flush_open(&self.rollups, &self.total_rollups);
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::collections::VecDeque;
+    use std::io;
+
+    use clap::Parser;
+
+    use super::*;
+
+    pub const RUNNING: u32 = (libc::IFF_UP | libc::IFF_RUNNING) as u32;
+    pub const DOWN: u32 = libc::IFF_UP as u32;
+
+    /// Hands out canned snapshots exactly one second apart.
+    pub struct FakeSampler {
+        ticks: VecDeque<Vec<InterfaceSample>>,
+        elapsed_us: u64,
+    }
+
+    impl InterfaceSampler for FakeSampler {
+        fn sample(&mut self) -> io::Result<Snapshot> {
+            let interfaces = self
+                .ticks
+                .pop_front()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "out of ticks"))?;
+            self.elapsed_us += 1_000_000;
+            Ok(Snapshot {
+                elapsed_us: self.elapsed_us,
+                time_us: 0,
+                interfaces,
+            })
+        }
+    }
+
+    pub fn iface(name: &str, rx_bytes: u64, tx_bytes: u64, flags: u32) -> InterfaceSample {
+        InterfaceSample {
+            name: name.to_string(),
+            rx_bytes,
+            tx_bytes,
+            flags,
+            is_physical: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn app(ticks: Vec<Vec<InterfaceSample>>) -> App {
+        let args = Args::parse_from(["macnetmon"]);
+        let sampler = FakeSampler {
+            ticks: ticks.into(),
+            elapsed_us: 0,
+        };
+        App::from_config(&args, Config::default(), Box::new(sampler))
+    }
+
+    /// Interfaces named by events of `kind`, oldest first.
+    fn events(app: &App, kind: EventKind) -> Vec<String> {
+        let mut names: Vec<String> = app
+            .events
+            .iter()
+            .filter(|event| event.kind == kind)
+            .filter_map(|event| event.iface.clone())
+            .collect();
+        names.reverse();
+        names
+    }
+
+    #[test]
+    fn counter_delta_handles_wraps_and_resets() {
+        assert_eq!(counter_delta(1_500, 1_000, false), Some(500));
+        assert_eq!(counter_delta(1_000, 1_000, true), Some(0));
+        // A 32-bit counter that passed 2^32 between samples.
+        assert_eq!(counter_delta(100, u32::MAX as u64 - 99, true), Some(200));
+        // 64-bit counters never wrap in practice; going back is a reset.
+        assert_eq!(counter_delta(100, u32::MAX as u64 - 99, false), None);
+        assert_eq!(counter_delta(5, 1 << 40, true), None);
+        // Wrapping would imply more than half the range moved: a reset.
+        assert_eq!(counter_delta(10, 1 << 31, true), None);
+        assert_eq!(counter_delta(0, (1 << 31) + 1, true), Some((1 << 31) - 1));
+    }
+
+    #[test]
+    fn rates_are_deltas_over_elapsed_time() {
+        let mut app = app(vec![
+            vec![iface("en0", 1_000, 500, RUNNING)],
+            vec![iface("en0", 3_000, 1_500, RUNNING)],
+            vec![iface("en0", 3_000, 1_500, RUNNING)],
+        ]);
+
+        app.update();
+        let en0 = &app.states["en0"];
+        assert_eq!((en0.rx_rate, en0.tx_rate), (0.0, 0.0));
+
+        app.update();
+        let en0 = &app.states["en0"];
+        assert_eq!((en0.rx_rate, en0.tx_rate), (2_000.0, 1_000.0));
+        assert_eq!(en0.total_rate, 3_000.0);
+        assert_eq!(en0.session_rx_bytes, 2_000);
+        assert_eq!(en0.rx_history.newest().and_then(|p| p.value), Some(2_000));
+        assert_eq!((app.total_rx, app.total_tx), (2_000.0, 1_000.0));
+
+        app.update();
+        assert_eq!(app.states["en0"].rx_rate, 0.0);
+        assert_eq!(app.states["en0"].rx_history.iter().count(), 3);
+    }
+
+    #[test]
+    fn interface_going_down_and_back_up() {
+        let mut app = app(vec![
+            vec![iface("en0", 0, 0, RUNNING)],
+            vec![iface("en0", 1_000, 0, DOWN)],
+            vec![iface("en0", 2_000, 0, DOWN)],
+            vec![iface("en0", 5_000, 0, RUNNING)],
+        ]);
+
+        app.update();
+        app.update();
+        let en0 = &app.states["en0"];
+        assert!(!en0.up);
+        assert_eq!(en0.rx_rate, 0.0);
+        assert_eq!(en0.transitions, 1);
+        assert_eq!(events(&app, EventKind::Down), ["en0"]);
+
+        app.update();
+        // History only grows while the link is up.
+        assert_eq!(app.states["en0"].rx_history.iter().count(), 1);
+
+        app.update();
+        let en0 = &app.states["en0"];
+        assert!(en0.up);
+        assert_eq!(en0.transitions, 2);
+        // Counters kept tracking while down, so this is one second's worth.
+        assert_eq!(en0.rx_rate, 3_000.0);
+        assert_eq!(en0.rx_history.iter().count(), 2);
+        assert_eq!(events(&app, EventKind::Up), ["en0"]);
+    }
+
+    #[test]
+    fn interfaces_appearing_and_disappearing() {
+        let mut app = app(vec![
+            vec![iface("en0", 0, 0, RUNNING)],
+            vec![iface("en0", 0, 0, RUNNING), iface("en1", 0, 0, RUNNING)],
+            vec![iface("en1", 100, 0, RUNNING)],
+        ]);
+
+        app.update();
+        // Everything is new on the first sample, so nothing is announced.
+        assert!(events(&app, EventKind::Appeared).is_empty());
+
+        app.update();
+        assert_eq!(events(&app, EventKind::Appeared), ["en1"]);
+        assert_eq!(app.states["en1"].rx_rate, 0.0);
+
+        app.update();
+        assert_eq!(events(&app, EventKind::Disappeared), ["en0"]);
+        assert!(!app.states.contains_key("en0"));
+        assert_eq!(app.states["en1"].rx_rate, 100.0);
+    }
+
+    #[test]
+    fn focus_moves_to_the_nearest_tile_and_stops_at_edges() {
+        let mut app = app(Vec::new());
+        // Two wide physical tiles above three narrower virtual ones.
+        app.tiles = [
+            ("en0", Rect::new(0, 0, 40, 10)),
+            ("en1", Rect::new(40, 0, 40, 10)),
+            ("utun0", Rect::new(0, 12, 26, 8)),
+            ("utun1", Rect::new(26, 12, 26, 8)),
+            ("utun2", Rect::new(52, 12, 28, 8)),
+        ]
+        .into_iter()
+        .map(|(name, rect)| (name.to_string(), rect))
+        .collect();
+
+        let mut path = Vec::new();
+        for dir in [
+            FocusMove::Up,
+            FocusMove::Right,
+            FocusMove::Right,
+            FocusMove::Up,
+            FocusMove::Down,
+            FocusMove::Down,
+            FocusMove::Left,
+            FocusMove::Left,
+            FocusMove::Left,
+            FocusMove::Up,
+        ] {
+            app.move_focus(dir);
+            path.push(app.focus.clone().unwrap());
+        }
+        assert_eq!(
+            path,
+            [
+                // Nothing focused: the first tile takes it.
+                "en0", "en1", "en1", "en1",
+                // Straight down lands on the tile under en1's centre.
+                "utun2", "utun2", "utun1", "utun0", "utun0",
+                // Back up to the tile above.
+                "en0",
+            ]
+        );
+    }
+
+    #[test]
+    fn pins_override_hide_patterns() {
+        let mut app = app(Vec::new());
+        app.hidden = vec!["utun*".to_string()];
+        app.pinned = vec!["utun3".to_string()];
+        assert!(app.is_hidden("utun0"));
+        assert!(!app.is_hidden("utun3"));
+        assert!(!app.is_hidden("en0"));
+
+        // --only still narrows the run, pinned or not.
+        app.only = vec!["en*".to_string()];
+        assert!(app.is_hidden("utun3"));
+        assert!(!app.is_hidden("en0"));
+    }
+
+    #[test]
+    fn utilization_follows_the_busier_direction() {
+        let mut en0 = IfaceState::new(&InterfaceSample {
+            link_speed: 1_000_000_000,
+            ..iface("en0", 0, 0, RUNNING)
+        });
+        assert_eq!(en0.capacity(), Some(125_000_000.0));
+        assert_eq!(en0.utilization(), Some(0.0));
+        en0.rx_rate = 25_000_000.0;
+        en0.tx_rate = 100_000_000.0;
+        assert_eq!(en0.utilization(), Some(80.0));
+        // Bursts above the nominal speed aren't capped.
+        en0.rx_rate = 250_000_000.0;
+        assert_eq!(en0.utilization(), Some(200.0));
+
+        // No known speed, no percentage.
+        en0.link_speed = 0;
+        assert_eq!((en0.capacity(), en0.utilization()), (None, None));
+    }
+
+    #[test]
+    fn primary_address_prefers_ipv4_then_routable_ipv6() {
+        let address = |text: &str| {
+            let address: IpAddr = text.parse().unwrap();
+            IfaceAddress {
+                family: if address.is_ipv4() {
+                    Family::Inet
+                } else {
+                    Family::Inet6
+                },
+                address,
+                prefix_len: 64,
+            }
+        };
+        let mut en0 = IfaceState::new(&iface("en0", 0, 0, RUNNING));
+        assert_eq!(en0.primary_address(), None);
+
+        en0.addresses = vec![address("fe80::1"), address("febf::2")];
+        assert_eq!(en0.primary_address(), None);
+
+        en0.addresses.push(address("2001:db8::1"));
+        en0.addresses.push(address("2001:db8::2"));
+        assert_eq!(en0.primary_address(), Some(&address("2001:db8::1")));
+
+        en0.addresses.push(address("10.0.0.7"));
+        assert_eq!(en0.primary_address(), Some(&address("10.0.0.7")));
+    }
 }
@@ -1,4 +1,12 @@
-use clap::Parser;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use crate::alert::AlertRule;
+use crate::query::{parse_span, QueryFormat};
+use crate::report::{Column, StreamFormat};
+use crate::trace::parse_speed;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -7,7 +15,10 @@ use clap::Parser;
     about = "Network interface bandwidth monitor"
 )]
 pub struct Args {
-    #[arg(short, long)]
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[arg(short, long, global = true)]
     pub interval: Option<u64>,
 
     #[arg(long)]
@@ -21,4 +32,93 @@ pub struct Args {
 
     #[arg(long)]
     pub bits: bool,
+
+    /// Add an alert rule for this run, e.g. "en0 rx > 50 MB/s for 10s" (repeatable)
+    #[arg(long = "alert", value_name = "RULE", global = true)]
+    pub alerts: Vec<AlertRule>,
+
+    /// Ring the terminal bell when an alert fires; saved to the config
+    #[arg(long)]
+    pub bell: bool,
+
+    /// Only show interfaces matching these globs for this run, e.g. `en*,utun3`
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub only: Vec<String>,
+
+    /// Hide interfaces matching these globs; added to the saved hidden list
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub exclude: Vec<String>,
+
+    /// Print a JSON snapshot of every interface and exit instead of starting the TUI
+    #[arg(long, conflicts_with = "format")]
+    pub json: bool,
+
+    /// Stream one record per interface per tick to stdout instead of starting the TUI
+    #[arg(long, value_enum)]
+    pub format: Option<StreamFormat>,
+
+    /// Columns to include in streamed records
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "time,name,rx_rate,tx_rate,total_rate,rx_bytes,tx_bytes",
+        requires = "format"
+    )]
+    pub columns: Vec<Column>,
+
+    /// Also stream an overview row named `total` with the summed rates each tick
+    #[arg(long, requires = "format")]
+    pub totals: bool,
+
+    /// Serve Prometheus metrics at http://<ADDR>/metrics while running
+    #[arg(long, value_name = "ADDR", global = true)]
+    pub prometheus_listen: Option<SocketAddr>,
+
+    /// Keep sampling without the TUI or any output, e.g. to feed --prometheus-listen
+    #[arg(long, conflicts_with_all = ["json", "format"])]
+    pub headless: bool,
+
+    /// Number of intervals to measure: once for `--json` by default, otherwise until interrupted
+    #[arg(short = 'n', long, global = true)]
+    pub count: Option<u64>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Sample without a terminal and append every tick to a JSON Lines trace
+    Record {
+        /// File to write the trace to
+        #[arg(short, long)]
+        out: PathBuf,
+    },
+
+    /// Play a recorded trace back through the TUI
+    Replay {
+        /// Trace written by `record`
+        trace: PathBuf,
+
+        /// Playback speed relative to the original recording
+        #[arg(short, long, default_value_t = 1.0, value_parser = parse_speed)]
+        speed: f64,
+    },
+
+    /// Print rates and traffic from the saved history (see `keep_history`) and exit
+    Query {
+        /// Interfaces to include, as names or globs, e.g. `en0,utun*` (default: all)
+        #[arg(long, value_delimiter = ',')]
+        iface: Vec<String>,
+
+        /// How far back to look, e.g. 90m, 12h or 7d
+        #[arg(long, default_value = "24h", value_parser = parse_span)]
+        since: u64,
+
+        /// Bucket size to aggregate into, e.g. 1m or 1h; one row per interface when omitted
+        #[arg(long, value_parser = parse_span)]
+        agg: Option<u64>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "table")]
+        format: QueryFormat,
+    },
 }
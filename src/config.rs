@@ -13,6 +13,31 @@ pub struct Config {
     pub display: DisplaySettings,
     pub sort_mode: SortMode,
     pub interval_ms: u64,
+    /// Interface names or globs that are always shown and sorted first.
+    pub pinned: Vec<String>,
+    /// Interface names or globs that are never shown.
+    pub hidden: Vec<String>,
+    /// Alert rules in the form `[IFACE] METRIC OP VALUE [for DURATION] [run COMMAND]`.
+    pub alerts: Vec<String>,
+    /// Ring the terminal bell when an alert fires.
+    pub alert_bell: bool,
+    /// Minimum time between two runs of the same rule's hook on the same
+    /// interface.
+    pub hook_cooldown_secs: u64,
+    /// How long a hook may run before it's killed.
+    pub hook_timeout_secs: u64,
+    /// Per-interface data allowance for each billing month, e.g. `"20 GB"`.
+    pub usage_quota: Option<String>,
+    /// Day of the month (1-28) the billing month starts on.
+    pub billing_day: u32,
+    /// Keep the minute and hour rollups on disk so the long sparkline
+    /// windows survive a restart.
+    pub keep_rollups: bool,
+    /// Record every sample to an on-disk history store and reload it on
+    /// startup.
+    pub keep_history: bool,
+    /// Days of samples the history store keeps.
+    pub history_retention_days: u64,
 }
 
 impl Default for Config {
@@ -22,25 +47,33 @@ impl Default for Config {
             display: DisplaySettings::default(),
             sort_mode: SortMode::Name,
             interval_ms: 1000,
+            pinned: Vec::new(),
+            hidden: Vec::new(),
+            alerts: Vec::new(),
+            alert_bell: false,
+            hook_cooldown_secs: 60,
+            hook_timeout_secs: 30,
+            usage_quota: None,
+            billing_day: 1,
+            keep_rollups: false,
+            keep_history: false,
+            history_retention_days: 7,
         }
     }
 }
 
 impl Config {
     fn path() -> Option<PathBuf> {
-        let home = std::env::var("HOME").ok()?;
-        let path = PathBuf::from(home).join(".config").join("macnetmon.json");
-        if let Some(parent) = path.parent() {
-            let _ = std::fs::create_dir_all(parent);
-        }
-        Some(path)
+        config_path("macnetmon.json")
     }
 
     pub fn load() -> Self {
         if let Some(path) = Self::path() {
             if let Ok(file) = std::fs::File::open(path) {
                 let reader = std::io::BufReader::new(file);
-                if let Ok(cfg) = serde_json::from_reader(reader) {
+                if let Ok(mut cfg) = serde_json::from_reader::<_, Config>(reader) {
+                    // Every month has a 28th, so later days are pulled back.
+                    cfg.billing_day = cfg.billing_day.clamp(1, 28);
                     return cfg;
                 }
             }
@@ -57,3 +90,84 @@ impl Config {
         }
     }
 }
+
+/// `file` under `~/.config`, creating the directory if needed.
+pub fn config_path(file: &str) -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    let dir = PathBuf::from(home).join(".config");
+    let _ = std::fs::create_dir_all(&dir);
+    Some(dir.join(file))
+}
+
+/// Shell-style match supporting `*` (any run of characters) and `?` (any one
+/// character), enough for patterns like `utun*` or `bridge?`.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            n = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+pub fn matches_any(patterns: &[String], name: &str) -> bool {
+    patterns.iter().any(|pattern| glob_match(pattern, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs_match_names() {
+        for (pattern, name, matches) in [
+            ("utun*", "utun0", true),
+            ("utun*", "utun", true),
+            ("utun*", "en0", false),
+            ("*0", "en0", true),
+            ("*0", "utun10", true),
+            ("*0", "en1", false),
+            ("bridge?", "bridge0", true),
+            ("bridge?", "bridge", false),
+            ("bridge?", "bridge10", false),
+            ("en0", "en0", true),
+            ("en0", "en01", false),
+            ("en0", "en", false),
+            ("e*n*0", "ethernet0", true),
+            ("*", "anything", true),
+            ("", "", true),
+            ("", "en0", false),
+        ] {
+            assert_eq!(
+                glob_match(pattern, name),
+                matches,
+                "{pattern:?} vs {name:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn any_pattern_can_match() {
+        let patterns = vec!["utun*".to_string(), "bridge?".to_string()];
+        assert!(matches_any(&patterns, "bridge1"));
+        assert!(matches_any(&patterns, "utun4"));
+        assert!(!matches_any(&patterns, "en0"));
+        assert!(!matches_any(&[], "en0"));
+    }
+}
@@ -3,3 +3,17 @@ pub const MIN_TILE_WIDTH: u16 = 48;
 pub const MIN_INTERVAL_MS: u64 = 100;
 pub const MAX_INTERVAL_MS: u64 = 10_000;
 pub const INTERVAL_STEP_MS: u64 = 250;
+pub const REPLAY_SEEK_SECS: f64 = 10.0;
+pub const EVENT_LOG_LEN: usize = 500;
+pub const EVENT_PANE_HEIGHT: u16 = 8;
+pub const EVENT_SCROLL_STEP: isize = 5;
+pub const ALERT_PANE_ROWS: usize = 4;
+pub const ALERT_FLASH_MS: u64 = 500;
+pub const HOOK_OUTPUT_LINES: usize = 5;
+pub const USAGE_SAVE_SECS: u64 = 60;
+pub const QUOTA_WARN_LEVELS: [u8; 2] = [80, 100];
+pub const USAGE_PANE_ROWS: usize = 6;
+pub const MINUTE_ROLLUPS: usize = 24 * 60;
+pub const HOUR_ROLLUPS: usize = 30 * 24;
+pub const SCROLLBACK_FAST_STEP: isize = 10;
+pub const SCROLLBACK_COLUMNS: usize = 4096;
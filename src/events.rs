@@ -0,0 +1,79 @@
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::constants::EVENT_LOG_LEN;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    Appeared,
+    Disappeared,
+    Up,
+    Down,
+    AddressChanged,
+    CounterReset,
+    AliasChanged,
+    Alert,
+    AlertCleared,
+    Hook,
+    Error,
+}
+
+/// Something worth telling the user about that happened between two samples.
+#[derive(Clone, Debug)]
+pub struct Event {
+    pub time: SystemTime,
+    pub kind: EventKind,
+    /// Interface the event is about, if any.
+    pub iface: Option<String>,
+    pub message: String,
+}
+
+impl Event {
+    /// Local wall-clock time of the event as `HH:MM:SS`.
+    pub fn clock(&self) -> String {
+        match local_time(self.time) {
+            Some(tm) => format!("{:02}:{:02}:{:02}", tm.tm_hour, tm.tm_min, tm.tm_sec),
+            None => "--:--:--".to_string(),
+        }
+    }
+}
+
+/// Breaks `time` down in the local time zone.
+pub fn local_time(time: SystemTime) -> Option<libc::tm> {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0) as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&secs, &mut tm) }.is_null() {
+        return None;
+    }
+    Some(tm)
+}
+
+/// Bounded, newest-first log of `Event`s.
+#[derive(Default)]
+pub struct EventLog {
+    events: VecDeque<Event>,
+}
+
+impl EventLog {
+    pub fn push(&mut self, kind: EventKind, iface: Option<&str>, message: impl Into<String>) {
+        self.events.push_front(Event {
+            time: SystemTime::now(),
+            kind,
+            iface: iface.map(str::to_string),
+            message: message.into(),
+        });
+        self.events.truncate(EVENT_LOG_LEN);
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Newest first.
+    pub fn iter(&self) -> impl Iterator<Item = &Event> {
+        self.events.iter()
+    }
+}
@@ -0,0 +1,151 @@
+use std::iter::{Chain, Rev};
+use std::slice;
+
+/// Fixed-capacity buffer that keeps the most recent values, overwriting the
+/// oldest once full. Pushing is O(1), and iteration runs newest first without
+/// moving anything.
+#[derive(Clone, Debug)]
+pub struct Ring<T> {
+    buf: Vec<T>,
+    capacity: usize,
+    /// Where the next value goes; once full, also the oldest value.
+    head: usize,
+}
+
+/// Newest-first iterator over a `Ring`.
+pub type Iter<'a, T> = Chain<Rev<slice::Iter<'a, T>>, Rev<slice::Iter<'a, T>>>;
+
+impl<T> Ring<T> {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: Vec::with_capacity(capacity),
+            capacity,
+            head: 0,
+        }
+    }
+
+    /// Adds `value` as the newest entry, dropping the oldest if full.
+    pub fn push(&mut self, value: T) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.buf.len() < self.capacity {
+            self.buf.push(value);
+        } else {
+            self.buf[self.head] = value;
+        }
+        self.head = (self.head + 1) % self.capacity;
+    }
+
+    pub fn clear(&mut self) {
+        self.buf.clear();
+        self.head = 0;
+    }
+
+    /// Entries from newest to oldest.
+    pub fn iter(&self) -> Iter<'_, T> {
+        // Everything before `head` is newer than everything from it on.
+        // Until the buffer first fills, `head` is its length.
+        let (recent, earlier) = self.buf.split_at(self.head.min(self.buf.len()));
+        recent.iter().rev().chain(earlier.iter().rev())
+    }
+
+    /// Entries held, at most the capacity. The app only ever walks a ring
+    /// through `iter`, so only the tests ask.
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn newest(&self) -> Option<&T> {
+        self.iter().next()
+    }
+
+    pub fn oldest(&self) -> Option<&T> {
+        self.iter().next_back()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Ring<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(capacity: usize, values: impl IntoIterator<Item = u32>) -> Ring<u32> {
+        let mut ring = Ring::with_capacity(capacity);
+        for value in values {
+            ring.push(value);
+        }
+        ring
+    }
+
+    fn contents(ring: &Ring<u32>) -> Vec<u32> {
+        ring.iter().copied().collect()
+    }
+
+    #[test]
+    fn iterates_newest_first_before_filling() {
+        let ring = filled(4, 1..=3);
+        assert_eq!(contents(&ring), [3, 2, 1]);
+        assert_eq!(
+            (ring.newest(), ring.oldest(), ring.len()),
+            (Some(&3), Some(&1), 3)
+        );
+
+        let empty = filled(4, []);
+        assert_eq!(
+            (empty.newest(), empty.oldest(), empty.len()),
+            (None, None, 0)
+        );
+    }
+
+    #[test]
+    fn overwrites_the_oldest_once_full() {
+        // Exactly full: the write position has just wrapped to the start.
+        let full = filled(4, 1..=4);
+        assert_eq!(contents(&full), [4, 3, 2, 1]);
+
+        // Past capacity, with the newest entries on both sides of the wrap.
+        let wrapped = filled(4, 1..=6);
+        assert_eq!(contents(&wrapped), [6, 5, 4, 3]);
+        assert_eq!(
+            wrapped.iter().rev().copied().collect::<Vec<_>>(),
+            [3, 4, 5, 6]
+        );
+        assert_eq!(
+            (wrapped.newest(), wrapped.oldest(), wrapped.len()),
+            (Some(&6), Some(&3), 4)
+        );
+
+        // Many times around.
+        assert_eq!(contents(&filled(4, 1..=103)), [103, 102, 101, 100]);
+    }
+
+    #[test]
+    fn tiny_capacities() {
+        let one = filled(1, 1..=5);
+        assert_eq!(contents(&one), [5]);
+        assert_eq!((one.newest(), one.oldest()), (Some(&5), Some(&5)));
+
+        let none = filled(0, 1..=5);
+        assert_eq!((none.len(), none.newest()), (0, None));
+    }
+
+    #[test]
+    fn clear_starts_over() {
+        let mut ring = filled(3, 1..=5);
+        ring.clear();
+        assert_eq!(ring.len(), 0);
+        ring.push(9);
+        ring.push(10);
+        assert_eq!(contents(&ring), [10, 9]);
+    }
+}
@@ -1,34 +1,67 @@
+mod alert;
 mod app;
 mod args;
 mod config;
 mod constants;
+mod events;
+mod history;
 mod model;
 mod net;
+mod prometheus;
+mod query;
+mod report;
+mod rollup;
+mod store;
 mod theme;
+mod trace;
 mod ui;
+mod usage;
 
-use std::io;
+use std::fs::File;
+use std::io::{self, Write};
 use std::time::{Duration, Instant};
 
 use clap::Parser;
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use ratatui::DefaultTerminal;
 
 use crate::app::App;
-use crate::args::Args;
-use crate::ui::ui;
+use crate::args::{Args, Command};
+use crate::constants::{EVENT_SCROLL_STEP, REPLAY_SEEK_SECS, SCROLLBACK_FAST_STEP};
+use crate::model::FocusMove;
+use crate::net::SystemSampler;
+use crate::prometheus::Exporter;
+use crate::query::Query;
+use crate::report::{Report, StreamFormat, StreamWriter};
+use crate::trace::{RecordingSampler, Replay};
+use crate::ui::{alert_flash, ui};
 
-fn run_app(terminal: &mut DefaultTerminal, args: Args) -> io::Result<()> {
-    let mut app = App::new(&args);
+/// How often to wake for input while nothing is scheduled, e.g. when a replay
+/// is paused.
+const IDLE_POLL: Duration = Duration::from_millis(250);
 
+fn run_app(terminal: &mut DefaultTerminal, mut app: App) -> io::Result<()> {
     app.update();
     let mut last_tick = Instant::now();
 
     loop {
         terminal.draw(|f| ui(f, &mut app))?;
+        if app.bell_pending {
+            app.bell_pending = false;
+            let mut out = io::stdout();
+            out.write_all(b"\x07")?;
+            out.flush()?;
+        }
 
-        let tick_rate = Duration::from_millis(app.interval_ms);
-        let timeout = tick_rate.saturating_sub(last_tick.elapsed());
+        let tick_rate = app.next_tick();
+        let mut timeout = match tick_rate {
+            Some(rate) => rate.saturating_sub(last_tick.elapsed()),
+            None => IDLE_POLL,
+        };
+        // Redraw on every flash of an alerting tile, not just on samples.
+        if app.alerts.any_firing() {
+            timeout = timeout.min(alert_flash().1);
+        }
         if event::poll(timeout)? {
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
@@ -60,7 +93,32 @@ fn run_app(terminal: &mut DefaultTerminal, args: Args) -> io::Result<()> {
                             app.display.show_split = !app.display.show_split;
                             app.save_config();
                         }
+                        KeyCode::Char('u') => {
+                            app.display.link_scale = !app.display.link_scale;
+                            app.save_config();
+                        }
+                        KeyCode::Char('e') => {
+                            app.display.show_events = !app.display.show_events;
+                            app.event_scroll = 0;
+                            app.save_config();
+                        }
+                        KeyCode::Char('d') => {
+                            app.display.show_usage = !app.display.show_usage;
+                            app.save_config();
+                        }
+                        KeyCode::Char('w') => {
+                            app.display.window = app.display.window.next();
+                            app.save_config();
+                        }
+                        KeyCode::PageUp => app.scroll_events(EVENT_SCROLL_STEP),
+                        KeyCode::PageDown => app.scroll_events(-EVENT_SCROLL_STEP),
                         KeyCode::Char('r') => app.refresh_aliases(),
+                        KeyCode::Char('p') => app.toggle_pin(),
+                        KeyCode::Char('x') => app.toggle_hide(),
+                        KeyCode::Char('H') => {
+                            app.display.show_hidden = !app.display.show_hidden;
+                            app.save_config();
+                        }
                         KeyCode::Char('+') | KeyCode::Char('=') => {
                             app.inc_interval();
                             last_tick = Instant::now();
@@ -69,13 +127,63 @@ fn run_app(terminal: &mut DefaultTerminal, args: Args) -> io::Result<()> {
                             app.dec_interval();
                             last_tick = Instant::now();
                         }
+                        KeyCode::Enter => {
+                            if app.detail.is_some() {
+                                app.close_detail();
+                            } else {
+                                app.open_detail();
+                            }
+                        }
+                        KeyCode::Esc => {
+                            if app.detail.is_some() {
+                                app.close_detail();
+                            } else {
+                                app.clear_focus();
+                            }
+                        }
+                        // While paused left and right scroll back through
+                        // history instead of moving the focus.
+                        KeyCode::Left | KeyCode::Right if app.scrollback.is_some() => {
+                            let step = if key.modifiers.contains(KeyModifiers::SHIFT) {
+                                SCROLLBACK_FAST_STEP
+                            } else {
+                                1
+                            };
+                            if key.code == KeyCode::Left {
+                                app.scroll_history(step);
+                            } else {
+                                app.scroll_history(-step);
+                            }
+                        }
+                        KeyCode::Left => app.move_focus(FocusMove::Left),
+                        KeyCode::Down => app.move_focus(FocusMove::Down),
+                        KeyCode::Up => app.move_focus(FocusMove::Up),
+                        KeyCode::Right => app.move_focus(FocusMove::Right),
+                        KeyCode::Tab => app.cycle_detail(true),
+                        KeyCode::BackTab => app.cycle_detail(false),
+                        KeyCode::Char(' ') => {
+                            app.toggle_pause();
+                            last_tick = Instant::now();
+                        }
+                        KeyCode::Char('[') => app.seek_replay(-REPLAY_SEEK_SECS),
+                        KeyCode::Char(']') => app.seek_replay(REPLAY_SEEK_SECS),
+                        KeyCode::Char('<') => {
+                            if let Some(replay) = app.sampler.replay() {
+                                replay.slower();
+                            }
+                        }
+                        KeyCode::Char('>') => {
+                            if let Some(replay) = app.sampler.replay() {
+                                replay.faster();
+                            }
+                        }
                         _ => {}
                     }
                 }
             }
         }
 
-        if last_tick.elapsed() >= tick_rate {
+        if tick_rate.is_some_and(|rate| last_tick.elapsed() >= rate) {
             app.update();
             last_tick = Instant::now();
         }
@@ -84,10 +192,114 @@ fn run_app(terminal: &mut DefaultTerminal, args: Args) -> io::Result<()> {
     Ok(())
 }
 
+/// Drives `app` on its interval without a terminal, calling `on_tick` after
+/// every sample. Runs until `count` samples have been taken, or forever.
+fn run_headless(
+    app: &mut App,
+    count: Option<u64>,
+    mut on_tick: impl FnMut(&App) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut taken = 0u64;
+
+    loop {
+        let tick_start = Instant::now();
+        app.update();
+        if let Some(err) = app.last_error.take() {
+            eprintln!("error: {err}");
+        }
+        on_tick(app)?;
+
+        taken += 1;
+        if count.is_some_and(|count| taken >= count) {
+            return Ok(());
+        }
+
+        let tick_rate = Duration::from_millis(app.interval_ms);
+        std::thread::sleep(tick_rate.saturating_sub(tick_start.elapsed()));
+    }
+}
+
+/// Measures `args.count` intervals (plus one baseline sample so the first
+/// interval has something to diff against) and prints the result as JSON.
+fn run_json(app: &mut App, args: &Args) -> io::Result<()> {
+    let count = args.count.unwrap_or(1).max(1);
+    run_headless(app, Some(count + 1), |_| Ok(()))?;
+    let stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(stdout, &Report::new(app))?;
+    println!();
+    Ok(())
+}
+
+/// Streams records to stdout every tick until `args.count` ticks have been
+/// written or the reader goes away.
+fn run_stream(app: &mut App, args: &Args, format: StreamFormat) -> io::Result<()> {
+    let mut writer = StreamWriter::new(io::stdout(), format, args.columns.clone(), args.totals);
+    // The first tick only establishes a baseline, so skip it.
+    let count = args.count.map(|count| count + 1);
+    let result = run_headless(app, count, |app| {
+        if app.sample_index > 1 {
+            writer.write_tick(app)?;
+        }
+        Ok(())
+    });
+    match result {
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        other => other,
+    }
+}
+
 fn main() -> io::Result<()> {
     let args = Args::parse();
+    let mut app = match &args.command {
+        Some(Command::Record { out }) => {
+            let sampler = RecordingSampler::new(SystemSampler::new(), File::create(out)?);
+            App::with_sampler(&args, Box::new(sampler))
+        }
+        Some(Command::Replay { trace, speed }) => {
+            let replay = Replay::load(trace, *speed)?;
+            App::with_sampler(&args, Box::new(replay))
+        }
+        Some(Command::Query {
+            iface,
+            since,
+            agg,
+            format,
+        }) => {
+            let query = Query {
+                ifaces: iface,
+                exclude: &args.exclude,
+                since: *since,
+                agg: *agg,
+                format: *format,
+            };
+            // Printed with Display so the hint in the message reads cleanly.
+            if let Err(err) = query::run(&query) {
+                eprintln!("error: {err}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        None => App::new(&args),
+    };
+    if let Some(addr) = args.prometheus_listen {
+        app.exporter = Some(Exporter::start(addr)?);
+    }
+
+    match &args.command {
+        Some(Command::Record { .. }) => return run_headless(&mut app, args.count, |_| Ok(())),
+        Some(Command::Replay { .. }) => {}
+        Some(Command::Query { .. }) => unreachable!("queries return before the app is built"),
+        None if args.json => return run_json(&mut app, &args),
+        None if args.headless => return run_headless(&mut app, args.count, |_| Ok(())),
+        None => {
+            if let Some(format) = args.format {
+                return run_stream(&mut app, &args, format);
+            }
+        }
+    }
+
     let mut terminal = ratatui::init();
-    let result = run_app(&mut terminal, args);
+    let result = run_app(&mut terminal, app);
     ratatui::restore();
     if let Err(err) = result {
         eprintln!("error: {}", err);
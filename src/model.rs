@@ -6,12 +6,175 @@ pub enum SortMode {
     Name,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Group {
     Physical,
     Virtual,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FocusMove {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// One history sample: a rate and the stretch of wall-clock time it was
+/// measured over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Point {
+    /// End of the interval, in Unix microseconds.
+    pub time_us: u64,
+    /// Length of the interval in microseconds.
+    pub dt_us: u64,
+    /// `None` marks a sample where the counters were reset and no rate could
+    /// be derived.
+    pub value: Option<u64>,
+}
+
+/// Places newest-first `points` on a time axis of up to `len` columns, each
+/// `step_us` wide, counting back from `now_us`. A column takes the average of
+/// the points overlapping it, weighted by overlap, so samples taken at a
+/// different interval still line up. Columns no point covers, or that overlap
+/// a gap, come back as `None`, except before the oldest point, where the
+/// series just ends.
+///
+/// Columns are worked out as they're pulled, straight from the points, so a
+/// sparkline can draw from a history without copying it.
+pub fn resample<'a, I>(points: I, now_us: u64, step_us: u64, len: usize) -> Resample<I::IntoIter>
+where
+    I: IntoIterator<Item = &'a Point>,
+    I::IntoIter: Clone,
+{
+    Resample {
+        points: points.into_iter(),
+        now_us,
+        step_us: step_us.max(1),
+        column: 0,
+        len,
+    }
+}
+
+/// Iterator returned by `resample`.
+#[derive(Clone, Debug)]
+pub struct Resample<I> {
+    /// Points that may still reach into the next column, newest first.
+    points: I,
+    now_us: u64,
+    step_us: u64,
+    column: usize,
+    len: usize,
+}
+
+impl<'a, I> Iterator for Resample<I>
+where
+    I: Iterator<Item = &'a Point> + Clone,
+{
+    type Item = Option<u64>;
+
+    fn next(&mut self) -> Option<Option<u64>> {
+        if self.column >= self.len {
+            return None;
+        }
+        let col_end = self
+            .now_us
+            .saturating_sub(self.column as u64 * self.step_us);
+        let col_start = col_end.saturating_sub(self.step_us);
+        // The stretch of time a point covers, clipped to `now_us`.
+        let span = |point: &Point| {
+            let end = point.time_us.min(self.now_us);
+            (point.time_us.saturating_sub(point.dt_us.max(1)), end)
+        };
+
+        // Points that end up wholly newer than this column are done with.
+        while let Some(point) = self.points.clone().next() {
+            let (start, end) = span(point);
+            if start < end && start < col_end {
+                break;
+            }
+            self.points.next();
+        }
+        // The series ends once no point reaches into what's left of it.
+        let oldest_us = self.now_us.saturating_sub(self.len as u64 * self.step_us);
+        let (_, end) = span(self.points.clone().next()?);
+        if end <= oldest_us {
+            return None;
+        }
+
+        let (mut sum, mut weight, mut gap) = (0u128, 0u64, false);
+        for point in self.points.clone() {
+            let (start, end) = span(point);
+            if end <= col_start {
+                break;
+            }
+            let overlap = end.min(col_end).saturating_sub(start.max(col_start));
+            if overlap == 0 {
+                continue;
+            }
+            match point.value {
+                Some(value) => {
+                    sum += value as u128 * overlap as u128;
+                    weight += overlap;
+                }
+                None => gap = true,
+            }
+        }
+        self.column += 1;
+        Some((!gap && weight > 0).then(|| (sum / weight as u128) as u64))
+    }
+}
+
+/// How far back the sparklines reach. The short window draws raw samples;
+/// the longer ones draw one-minute rollups.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Window {
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    Hour,
+    #[serde(rename = "24h")]
+    Day,
+}
+
+impl Window {
+    pub fn secs(self) -> u64 {
+        match self {
+            Window::FiveMinutes => 5 * 60,
+            Window::Hour => 60 * 60,
+            Window::Day => 24 * 60 * 60,
+        }
+    }
+
+    /// Seconds each sparkline column covers in the windows drawn from the
+    /// minute rollups, the same in every pane so a scrollback cursor means
+    /// one moment everywhere. The 5m window steps by the sample interval
+    /// instead; for it this is just the rollup width.
+    pub fn rollup_column_secs(self) -> u64 {
+        match self {
+            Window::FiveMinutes | Window::Hour => 60,
+            Window::Day => 15 * 60,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Window::FiveMinutes => "5m",
+            Window::Hour => "1h",
+            Window::Day => "24h",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Window::FiveMinutes => Window::Hour,
+            Window::Hour => Window::Day,
+            Window::Day => Window::FiveMinutes,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 #[serde(default)]
 pub struct DisplaySettings {
@@ -21,6 +184,12 @@ pub struct DisplaySettings {
     pub show_inactive: bool,
     pub show_bits: bool,
     pub show_split: bool,
+    pub show_hidden: bool,
+    /// Scale interface sparklines to link capacity instead of the window max.
+    pub link_scale: bool,
+    pub show_events: bool,
+    pub show_usage: bool,
+    pub window: Window,
 }
 
 impl Default for DisplaySettings {
@@ -32,6 +201,70 @@ impl Default for DisplaySettings {
             show_inactive: false,
             show_bits: false,
             show_split: true,
+            show_hidden: false,
+            link_scale: false,
+            show_events: false,
+            show_usage: false,
+            window: Window::FiveMinutes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: u64 = 1_000_000;
+
+    fn point(secs: u64, dt_secs: u64, value: Option<u64>) -> Point {
+        Point {
+            time_us: secs * SEC,
+            dt_us: dt_secs * SEC,
+            value,
         }
     }
+
+    fn columns(points: &[Point], now_secs: u64, step_secs: u64, len: usize) -> Vec<Option<u64>> {
+        resample(points, now_secs * SEC, step_secs * SEC, len).collect()
+    }
+
+    #[test]
+    fn one_point_per_column() {
+        let points = [
+            point(10, 1, Some(100)),
+            point(9, 1, Some(200)),
+            point(8, 1, Some(300)),
+        ];
+        assert_eq!(
+            columns(&points, 10, 1, 10),
+            [Some(100), Some(200), Some(300)]
+        );
+        assert_eq!(columns(&points, 10, 1, 2), [Some(100), Some(200)]);
+    }
+
+    #[test]
+    fn merges_and_stretches_by_overlap() {
+        // Two one-second points share a two-second column.
+        let short = [point(10, 1, Some(100)), point(9, 1, Some(300))];
+        assert_eq!(columns(&short, 10, 2, 10), [Some(200)]);
+        // One three-second point covers three one-second columns.
+        let long = [point(10, 3, Some(100))];
+        assert_eq!(columns(&long, 10, 1, 10), [Some(100); 3]);
+    }
+
+    #[test]
+    fn gaps_and_holes_come_back_empty() {
+        let points = [
+            point(10, 1, Some(100)),
+            point(9, 1, None),
+            point(6, 1, Some(50)),
+        ];
+        assert_eq!(
+            columns(&points, 10, 1, 10),
+            [Some(100), None, None, None, Some(50)]
+        );
+        // Nothing inside the window: no columns at all.
+        assert!(columns(&points, 10, 1, 0).is_empty());
+        assert!(columns(&points, 20, 1, 5).is_empty());
+    }
 }
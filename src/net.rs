@@ -1,20 +1,205 @@
-use std::collections::HashMap;
-use std::ffi::CStr;
+use std::fmt;
 use std::io;
-use std::process::Command;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
-#[derive(Clone, Debug)]
+use serde::{Deserialize, Serialize};
+
+use crate::trace::Replay;
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+
+#[cfg(target_os = "linux")]
+use linux as platform;
+#[cfg(target_os = "macos")]
+use macos as platform;
+
+pub use platform::load_interface_aliases;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Family {
+    Inet,
+    Inet6,
+}
+
+/// An IP address bound to an interface, with its prefix length taken from the
+/// netmask.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IfaceAddress {
+    pub family: Family,
+    pub address: IpAddr,
+    pub prefix_len: u8,
+}
+
+impl fmt::Display for IfaceAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix_len)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct InterfaceSample {
     pub name: String,
     pub rx_bytes: u64,
     pub tx_bytes: u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_drops: u64,
+    pub tx_drops: u64,
+    pub collisions: u64,
+    pub rx_multicast: u64,
+    pub tx_multicast: u64,
+    /// Nominal link speed in bits per second, or 0 when the driver doesn't
+    /// report one (loopback, tunnels, most virtual devices).
+    pub link_speed: u64,
+    pub mtu: u32,
+    /// The counters are 32 bits wide and wrap at 2^32 rather than 2^64.
+    pub wrap32: bool,
+    /// Link-layer address as colon-separated hex, if the interface has one.
+    pub mac: Option<String>,
+    pub addresses: Vec<IfaceAddress>,
     pub flags: u32,
     pub is_loopback: bool,
+    /// Backed by real hardware rather than a tunnel, bridge or other virtual
+    /// device. Each platform backend decides this its own way.
+    pub is_physical: bool,
+}
+
+/// One pass over every interface, stamped with a monotonic clock so rates can
+/// be derived without the consumer having to time the call itself.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Microseconds since the sampler was created.
+    pub elapsed_us: u64,
+    /// Wall-clock time of the sample in microseconds since the Unix epoch;
+    /// 0 in traces recorded before this was added.
+    #[serde(default)]
+    pub time_us: u64,
+    pub interfaces: Vec<InterfaceSample>,
+}
+
+/// A source of interface counters. `App` owns one of these and calls it once
+/// per tick; anything that can produce a `Snapshot` can drive the UI.
+pub trait InterfaceSampler {
+    fn sample(&mut self) -> io::Result<Snapshot>;
+
+    /// Playback controls, for samplers that read back a recorded trace.
+    fn replay(&mut self) -> Option<&mut Replay> {
+        None
+    }
+}
+
+/// Live sampler for the host platform: `getifaddrs` on macOS, procfs and sysfs
+/// on Linux.
+pub struct SystemSampler {
+    start: Instant,
+}
+
+impl SystemSampler {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InterfaceSampler for SystemSampler {
+    fn sample(&mut self) -> io::Result<Snapshot> {
+        let elapsed_us = self.start.elapsed().as_micros() as u64;
+        let time_us = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .unwrap_or(0);
+        let interfaces = platform::sample_interfaces()?;
+        Ok(Snapshot {
+            elapsed_us,
+            time_us,
+            interfaces,
+        })
+    }
+}
+
+/// Decodes the address of an `AF_INET` or `AF_INET6` ifaddrs entry. Anything
+/// else, including entries without an address, yields `None`.
+fn ifaddr_address(ifa: &libc::ifaddrs) -> Option<IfaceAddress> {
+    if ifa.ifa_addr.is_null() {
+        return None;
+    }
+    let family = unsafe { (*ifa.ifa_addr).sa_family as i32 };
+    let netmask = (!ifa.ifa_netmask.is_null()).then_some(ifa.ifa_netmask);
+
+    if family == libc::AF_INET {
+        let sin = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in) };
+        Some(IfaceAddress {
+            family: Family::Inet,
+            address: IpAddr::V4(Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr))),
+            prefix_len: netmask.map_or(32, |mask| {
+                let mask = unsafe { &*(mask as *const libc::sockaddr_in) };
+                mask.sin_addr.s_addr.count_ones() as u8
+            }),
+        })
+    } else if family == libc::AF_INET6 {
+        let sin6 = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in6) };
+        let octets = sin6.sin6_addr.s6_addr;
+        // The BSD stack embeds the scope id in the second 16-bit group of
+        // link-local addresses; it is not part of the address proper.
+        #[cfg(target_os = "macos")]
+        let octets = {
+            let mut octets = octets;
+            if octets[0] == 0xfe && octets[1] & 0xc0 == 0x80 {
+                octets[2] = 0;
+                octets[3] = 0;
+            }
+            octets
+        };
+        Some(IfaceAddress {
+            family: Family::Inet6,
+            address: IpAddr::V6(Ipv6Addr::from(octets)),
+            prefix_len: netmask.map_or(128, |mask| {
+                let mask = unsafe { &*(mask as *const libc::sockaddr_in6) };
+                mask.sin6_addr
+                    .s6_addr
+                    .iter()
+                    .map(|b| b.count_ones() as u8)
+                    .sum()
+            }),
+        })
+    } else {
+        None
+    }
+}
+
+/// Formats a hardware address as `aa:bb:cc:dd:ee:ff`, treating an empty or
+/// all-zero address (loopback, tunnels) as none at all.
+fn format_mac(bytes: &[u8]) -> Option<String> {
+    if bytes.iter().all(|b| *b == 0) {
+        return None;
+    }
+    Some(
+        bytes
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<Vec<_>>()
+            .join(":"),
+    )
 }
 
 pub fn get_hostname() -> Option<String> {
     let mut buf = [0u8; 256];
-    let rc = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut i8, buf.len()) };
+    let rc = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
     if rc != 0 {
         return None;
     }
@@ -26,83 +211,106 @@ pub fn is_up(flags: u32) -> bool {
     (flags & libc::IFF_UP as u32) != 0
 }
 
-pub fn is_physical_interface(name: &str) -> bool {
-    if !name.starts_with("en") || name.len() <= 2 {
-        return false;
-    }
-    name[2..].chars().all(|c| c.is_ascii_digit())
+/// Up and with a link: what ifconfig shows as `<UP,...,RUNNING>`.
+pub fn is_running(flags: u32) -> bool {
+    is_up(flags) && (flags & libc::IFF_RUNNING as u32) != 0
 }
 
-pub fn sample_interfaces() -> io::Result<Vec<InterfaceSample>> {
-    let mut addrs: *mut libc::ifaddrs = std::ptr::null_mut();
-    let result = unsafe { libc::getifaddrs(&mut addrs) };
-    if result != 0 {
-        return Err(io::Error::last_os_error());
-    }
+const FLAG_NAMES: [(libc::c_int, &str); 10] = [
+    (libc::IFF_UP, "UP"),
+    (libc::IFF_BROADCAST, "BROADCAST"),
+    (libc::IFF_DEBUG, "DEBUG"),
+    (libc::IFF_LOOPBACK, "LOOPBACK"),
+    (libc::IFF_POINTOPOINT, "POINTOPOINT"),
+    (libc::IFF_RUNNING, "RUNNING"),
+    (libc::IFF_NOARP, "NOARP"),
+    (libc::IFF_PROMISC, "PROMISC"),
+    (libc::IFF_ALLMULTI, "ALLMULTI"),
+    (libc::IFF_MULTICAST, "MULTICAST"),
+];
 
-    let mut samples = Vec::new();
-    let mut current = addrs;
+/// Names of the `IFF_*` bits set in `flags`, in ifconfig order.
+pub fn flag_names(flags: u32) -> Vec<&'static str> {
+    FLAG_NAMES
+        .iter()
+        .filter(|(bit, _)| flags & *bit as u32 != 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
 
-    while !current.is_null() {
-        let ifa = unsafe { &*current };
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if ifa.ifa_addr.is_null() || ifa.ifa_name.is_null() {
-            current = ifa.ifa_next;
-            continue;
-        }
+    #[test]
+    fn macs_are_colon_separated_hex() {
+        assert_eq!(
+            format_mac(&[0x00, 0x1b, 0x63, 0xaa, 0x0f, 0xff]).as_deref(),
+            Some("00:1b:63:aa:0f:ff")
+        );
+        assert_eq!(format_mac(&[0; 6]), None);
+        assert_eq!(format_mac(&[]), None);
+    }
 
-        let family = unsafe { (*ifa.ifa_addr).sa_family as i32 };
-        if family == libc::AF_LINK && !ifa.ifa_data.is_null() {
-            let data = unsafe { &*(ifa.ifa_data as *const libc::if_data) };
-            let name = unsafe { CStr::from_ptr(ifa.ifa_name) }
-                .to_string_lossy()
-                .to_string();
-            let flags = ifa.ifa_flags;
-            let is_loopback = (flags & libc::IFF_LOOPBACK as u32) != 0;
-
-            samples.push(InterfaceSample {
-                name,
-                rx_bytes: data.ifi_ibytes as u64,
-                tx_bytes: data.ifi_obytes as u64,
-                flags,
-                is_loopback,
-            });
+    /// Runs `ifaddr_address` on an entry for `addr` and `mask`.
+    fn decode<T>(addr: &T, mask: Option<&T>) -> Option<IfaceAddress> {
+        let mut ifa: libc::ifaddrs = unsafe { std::mem::zeroed() };
+        ifa.ifa_addr = addr as *const T as *mut libc::sockaddr;
+        if let Some(mask) = mask {
+            ifa.ifa_netmask = mask as *const T as *mut libc::sockaddr;
         }
-
-        current = ifa.ifa_next;
+        ifaddr_address(&ifa)
     }
 
-    unsafe { libc::freeifaddrs(addrs) };
+    fn sockaddr_in6(octets: [u8; 16]) -> libc::sockaddr_in6 {
+        let mut sin6: libc::sockaddr_in6 = unsafe { std::mem::zeroed() };
+        sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+        sin6.sin6_addr.s6_addr = octets;
+        sin6
+    }
 
-    Ok(samples)
-}
+    #[test]
+    fn decodes_addresses_and_prefixes() {
+        let mut sin: libc::sockaddr_in = unsafe { std::mem::zeroed() };
+        sin.sin_family = libc::AF_INET as libc::sa_family_t;
+        sin.sin_addr.s_addr = u32::from(Ipv4Addr::new(192, 168, 1, 20)).to_be();
+        let mut mask = sin;
+        mask.sin_addr.s_addr = u32::from(Ipv4Addr::new(255, 255, 255, 0)).to_be();
+        assert_eq!(
+            decode(&sin, Some(&mask)).map(|a| a.to_string()).as_deref(),
+            Some("192.168.1.20/24")
+        );
+        assert_eq!(
+            decode(&sin, None).map(|a| a.to_string()).as_deref(),
+            Some("192.168.1.20/32")
+        );
 
-pub fn load_interface_aliases() -> io::Result<HashMap<String, String>> {
-    let output = Command::new("networksetup")
-        .arg("-listallhardwareports")
-        .output()?;
+        let global: Ipv6Addr = "2001:db8::1".parse().unwrap();
+        let mask: Ipv6Addr = "ffff:ffff:ffff:ffff::".parse().unwrap();
+        let decoded = decode(
+            &sockaddr_in6(global.octets()),
+            Some(&sockaddr_in6(mask.octets())),
+        );
+        assert_eq!(
+            decoded.map(|a| a.to_string()).as_deref(),
+            Some("2001:db8::1/64")
+        );
 
-    if !output.status.success() {
-        return Err(io::Error::new(io::ErrorKind::Other, "networksetup failed"));
+        let mut other: libc::sockaddr = unsafe { std::mem::zeroed() };
+        other.sa_family = libc::AF_UNIX as libc::sa_family_t;
+        assert_eq!(decode(&other, None), None);
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut map = HashMap::new();
-    let mut current_port: Option<String> = None;
-
-    for line in stdout.lines() {
-        let line = line.trim();
-        if let Some(rest) = line.strip_prefix("Hardware Port:") {
-            current_port = Some(rest.trim().to_string());
-        } else if let Some(rest) = line.strip_prefix("Device:") {
-            let dev = rest.trim();
-            if !dev.is_empty() {
-                if let Some(port) = current_port.take() {
-                    map.insert(dev.to_string(), port);
-                }
-            }
-        }
+    #[test]
+    fn scope_ids_are_scrubbed_only_where_embedded() {
+        // fe80:4::1, as macOS reports fe80::1%en0 when en0 is interface 4.
+        let link_local: Ipv6Addr = "fe80:4::1".parse().unwrap();
+        let decoded = decode(&sockaddr_in6(link_local.octets()), None).unwrap();
+        let expected = if cfg!(target_os = "macos") {
+            "fe80::1"
+        } else {
+            "fe80:4::1"
+        };
+        assert_eq!(decoded.address.to_string(), expected);
     }
-
-    Ok(map)
 }
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::{format_mac, ifaddr_address, is_up, IfaceAddress, InterfaceSample};
+
+const PROC_NET_DEV: &str = "/proc/net/dev";
+const SYS_CLASS_NET: &str = "/sys/class/net";
+
+#[derive(Clone, Copy, Debug, Default)]
+struct DevStats {
+    rx_bytes: u64,
+    tx_bytes: u64,
+    rx_packets: u64,
+    tx_packets: u64,
+    rx_errors: u64,
+    tx_errors: u64,
+    rx_drops: u64,
+    tx_drops: u64,
+    collisions: u64,
+    rx_multicast: u64,
+}
+
+/// Parses the kernel's `/proc/net/dev` table. The first two lines are headers;
+/// every other line is `name: rx_bytes rx_packets ... tx_bytes tx_packets ...`
+/// with eight receive columns followed by eight transmit columns.
+fn parse_proc_net_dev(text: &str) -> Vec<(String, DevStats)> {
+    let mut out = Vec::new();
+    for line in text.lines().skip(2) {
+        let Some((name, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let fields: Vec<u64> = rest
+            .split_whitespace()
+            .map(|f| f.parse().unwrap_or(0))
+            .collect();
+        if fields.len() < 16 {
+            continue;
+        }
+        out.push((
+            name.trim().to_string(),
+            DevStats {
+                rx_bytes: fields[0],
+                rx_packets: fields[1],
+                rx_errors: fields[2],
+                rx_drops: fields[3],
+                rx_multicast: fields[7],
+                tx_bytes: fields[8],
+                tx_packets: fields[9],
+                tx_errors: fields[10],
+                tx_drops: fields[11],
+                collisions: fields[13],
+            },
+        ));
+    }
+    out
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// Bits per second from a sysfs `speed` file, which is in Mb/s and reads as
+/// -1 (or fails with EINVAL) when the link is down or the driver has no
+/// notion of one. Unknown speeds come back as 0.
+fn parse_speed(text: &str) -> u64 {
+    text.parse::<i64>()
+        .ok()
+        .filter(|mbps| *mbps > 0)
+        .map_or(0, |mbps| mbps as u64 * 1_000_000)
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    read_trimmed(path)?.parse().ok()
+}
+
+fn read_hex(path: &Path) -> Option<u32> {
+    let text = read_trimmed(path)?;
+    u32::from_str_radix(text.trim_start_matches("0x"), 16).ok()
+}
+
+/// Fallback for systems where `/proc` is not mounted: walk sysfs and read the
+/// per-interface `statistics` directory instead.
+fn read_sysfs_statistics() -> io::Result<Vec<(String, DevStats)>> {
+    let mut out = Vec::new();
+    for entry in fs::read_dir(SYS_CLASS_NET)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let stats = entry.path().join("statistics");
+        let stat = |file: &str| read_u64(&stats.join(file)).unwrap_or(0);
+        out.push((
+            name,
+            DevStats {
+                rx_bytes: stat("rx_bytes"),
+                tx_bytes: stat("tx_bytes"),
+                rx_packets: stat("rx_packets"),
+                tx_packets: stat("tx_packets"),
+                rx_errors: stat("rx_errors"),
+                tx_errors: stat("tx_errors"),
+                rx_drops: stat("rx_dropped"),
+                tx_drops: stat("tx_dropped"),
+                collisions: stat("collisions"),
+                rx_multicast: stat("multicast"),
+            },
+        ));
+    }
+    Ok(out)
+}
+
+/// IPv4 and IPv6 addresses per interface from `getifaddrs`. Counters come
+/// from procfs, so this walk only looks at the address entries.
+fn ip_addresses() -> HashMap<String, Vec<IfaceAddress>> {
+    let mut map: HashMap<String, Vec<IfaceAddress>> = HashMap::new();
+    let mut addrs: *mut libc::ifaddrs = std::ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut addrs) } != 0 {
+        return map;
+    }
+
+    let mut current = addrs;
+    while !current.is_null() {
+        let ifa = unsafe { &*current };
+        if !ifa.ifa_name.is_null() {
+            if let Some(address) = ifaddr_address(ifa) {
+                let name = unsafe { CStr::from_ptr(ifa.ifa_name) }
+                    .to_string_lossy()
+                    .to_string();
+                map.entry(name).or_default().push(address);
+            }
+        }
+        current = ifa.ifa_next;
+    }
+
+    unsafe { libc::freeifaddrs(addrs) };
+    map
+}
+
+/// Parses sysfs `address`, which is colon-separated hex.
+fn read_mac(path: &Path) -> Option<String> {
+    let text = read_trimmed(path)?;
+    let bytes = text
+        .split(':')
+        .map(|part| u8::from_str_radix(part, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    format_mac(&bytes)
+}
+
+pub fn sample_interfaces() -> io::Result<Vec<InterfaceSample>> {
+    let devices = match fs::read_to_string(PROC_NET_DEV) {
+        Ok(text) => parse_proc_net_dev(&text),
+        Err(_) => read_sysfs_statistics()?,
+    };
+    let mut addresses = ip_addresses();
+
+    let mut samples = Vec::with_capacity(devices.len());
+    for (name, stats) in devices {
+        let dir = Path::new(SYS_CLASS_NET).join(&name);
+
+        // sysfs reports the administrative flags only; link state lives in
+        // `operstate`, so fold it back in as IFF_RUNNING. Loopback and tunnels
+        // without carrier detection report "unknown".
+        let mut flags = read_hex(&dir.join("flags")).unwrap_or(0);
+        let operstate = read_trimmed(&dir.join("operstate"));
+        if is_up(flags) && matches!(operstate.as_deref(), Some("up") | Some("unknown")) {
+            flags |= libc::IFF_RUNNING as u32;
+        }
+
+        let if_type = read_u64(&dir.join("type"));
+        let is_loopback = (flags & libc::IFF_LOOPBACK as u32) != 0
+            || if_type == Some(libc::ARPHRD_LOOPBACK as u64);
+        // Hardware-backed interfaces have a `device` link into the bus they
+        // hang off; bridges, veths, tunnels and the like live under
+        // /sys/devices/virtual and have none.
+        let is_physical = !is_loopback && dir.join("device").exists();
+
+        let link_speed = read_trimmed(&dir.join("speed")).map_or(0, |s| parse_speed(&s));
+        let mtu = read_u64(&dir.join("mtu")).unwrap_or(0) as u32;
+        let mac = read_mac(&dir.join("address"));
+        let iface_addresses = addresses.remove(&name).unwrap_or_default();
+
+        samples.push(InterfaceSample {
+            name,
+            rx_bytes: stats.rx_bytes,
+            tx_bytes: stats.tx_bytes,
+            rx_packets: stats.rx_packets,
+            tx_packets: stats.tx_packets,
+            rx_errors: stats.rx_errors,
+            tx_errors: stats.tx_errors,
+            rx_drops: stats.rx_drops,
+            tx_drops: stats.tx_drops,
+            collisions: stats.collisions,
+            rx_multicast: stats.rx_multicast,
+            // Linux only counts received multicast per interface.
+            tx_multicast: 0,
+            link_speed,
+            mtu,
+            wrap32: false,
+            mac,
+            addresses: iface_addresses,
+            flags,
+            is_loopback,
+            is_physical,
+        });
+    }
+
+    Ok(samples)
+}
+
+/// Linux has no hardware-port names like macOS, but interfaces may carry an
+/// administrator-set description in `ifalias`.
+pub fn load_interface_aliases() -> io::Result<HashMap<String, String>> {
+    let mut map = HashMap::new();
+    for entry in fs::read_dir(SYS_CLASS_NET)? {
+        let entry = entry?;
+        if let Some(alias) = read_trimmed(&entry.path().join("ifalias")) {
+            if !alias.is_empty() {
+                map.insert(entry.file_name().to_string_lossy().to_string(), alias);
+            }
+        }
+    }
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:  123456     789    0    0    0     0          0         0   123456     789    0    0    0     0       0          0
+  eth0: 987654321 654321  11   22   33    44         55        66 123456789 98765   77   88   99   100     111        122
+wlan0: 1 2 3
+";
+
+    #[test]
+    fn parses_receive_and_transmit_columns() {
+        let stats = parse_proc_net_dev(SAMPLE);
+        let names: Vec<&str> = stats.iter().map(|(name, _)| name.as_str()).collect();
+        // Short lines are skipped rather than read as zeros.
+        assert_eq!(names, ["lo", "eth0"]);
+
+        let eth0 = stats[1].1;
+        assert_eq!((eth0.rx_bytes, eth0.rx_packets), (987_654_321, 654_321));
+        assert_eq!((eth0.rx_errors, eth0.rx_drops), (11, 22));
+        assert_eq!(eth0.rx_multicast, 66);
+        assert_eq!((eth0.tx_bytes, eth0.tx_packets), (123_456_789, 98_765));
+        assert_eq!((eth0.tx_errors, eth0.tx_drops), (77, 88));
+        assert_eq!(eth0.collisions, 100);
+    }
+
+    #[test]
+    fn link_speeds_are_megabits() {
+        assert_eq!(parse_speed("1000"), 1_000_000_000);
+        assert_eq!(parse_speed("25000"), 25_000_000_000);
+        for text in ["-1", "0", "", "unknown"] {
+            assert_eq!(parse_speed(text), 0, "{text:?}");
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::io;
+use std::process::Command;
+
+use super::{format_mac, ifaddr_address, IfaceAddress, InterfaceSample};
+
+fn is_physical_interface(name: &str) -> bool {
+    if !name.starts_with("en") || name.len() <= 2 {
+        return false;
+    }
+    name[2..].chars().all(|c| c.is_ascii_digit())
+}
+
+/// Link speeds by interface name, read from the routing socket's 64-bit
+/// interface data. The `if_data` that `getifaddrs` hands out keeps the speed
+/// in 32 bits, which caps 10 Gb/s and faster links at about 4.3 Gb/s.
+fn link_speeds() -> io::Result<HashMap<String, u64>> {
+    let mut mib = [libc::CTL_NET, libc::PF_ROUTE, 0, 0, libc::NET_RT_IFLIST2, 0];
+    let mut sysctl = |buf: *mut libc::c_void, len: &mut libc::size_t| {
+        let mib_len = mib.len() as libc::c_uint;
+        let result =
+            unsafe { libc::sysctl(mib.as_mut_ptr(), mib_len, buf, len, std::ptr::null_mut(), 0) };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    };
+    let mut len: libc::size_t = 0;
+    sysctl(std::ptr::null_mut(), &mut len)?;
+    let mut buf = vec![0u8; len];
+    sysctl(buf.as_mut_ptr() as *mut libc::c_void, &mut len)?;
+    buf.truncate(len);
+
+    // The buffer is a run of variable-length messages, each starting with its
+    // length and type; only RTM_IFINFO2 carries `if_data64`.
+    let mut speeds = HashMap::new();
+    let mut offset = 0;
+    while offset + 4 <= buf.len() {
+        let msg_len = u16::from_ne_bytes([buf[offset], buf[offset + 1]]) as usize;
+        if msg_len == 0 {
+            break;
+        }
+        let msg_type = buf[offset + 3] as i32;
+        if msg_type == libc::RTM_IFINFO2
+            && offset + std::mem::size_of::<libc::if_msghdr2>() <= buf.len()
+        {
+            let msg = unsafe {
+                std::ptr::read_unaligned(buf.as_ptr().add(offset) as *const libc::if_msghdr2)
+            };
+            let mut name = [0 as libc::c_char; libc::IF_NAMESIZE];
+            let found = unsafe { libc::if_indextoname(msg.ifm_index as u32, name.as_mut_ptr()) };
+            if !found.is_null() {
+                let name = unsafe { CStr::from_ptr(name.as_ptr()) };
+                speeds.insert(
+                    name.to_string_lossy().to_string(),
+                    msg.ifm_data.ifi_baudrate,
+                );
+            }
+        }
+        offset += msg_len;
+    }
+    Ok(speeds)
+}
+
+pub fn sample_interfaces() -> io::Result<Vec<InterfaceSample>> {
+    // Best effort: fall back to the 32-bit speed if the sysctl fails, e.g.
+    // when an interface appears between sizing the buffer and filling it.
+    let speeds = link_speeds().unwrap_or_default();
+
+    let mut addrs: *mut libc::ifaddrs = std::ptr::null_mut();
+    let result = unsafe { libc::getifaddrs(&mut addrs) };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut samples = Vec::new();
+    let mut addresses: HashMap<String, Vec<IfaceAddress>> = HashMap::new();
+    let mut current = addrs;
+
+    while !current.is_null() {
+        let ifa = unsafe { &*current };
+
+        if ifa.ifa_addr.is_null() || ifa.ifa_name.is_null() {
+            current = ifa.ifa_next;
+            continue;
+        }
+
+        let name = unsafe { CStr::from_ptr(ifa.ifa_name) }
+            .to_string_lossy()
+            .to_string();
+
+        // The same walk yields one entry per address: AF_INET and AF_INET6
+        // entries are gathered here and attached to the AF_LINK sample below.
+        if let Some(address) = ifaddr_address(ifa) {
+            addresses.entry(name).or_default().push(address);
+            current = ifa.ifa_next;
+            continue;
+        }
+
+        let family = unsafe { (*ifa.ifa_addr).sa_family as i32 };
+        if family == libc::AF_LINK && !ifa.ifa_data.is_null() {
+            let data = unsafe { &*(ifa.ifa_data as *const libc::if_data) };
+            // sockaddr_dl packs the name and then the hardware address into
+            // `sdl_data`, which may run past the declared array.
+            let sdl = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_dl) };
+            let mac = unsafe {
+                let base = sdl.sdl_data.as_ptr() as *const u8;
+                std::slice::from_raw_parts(base.add(sdl.sdl_nlen as usize), sdl.sdl_alen as usize)
+            };
+            let flags = ifa.ifa_flags;
+            let is_loopback = (flags & libc::IFF_LOOPBACK as u32) != 0;
+            let is_physical = is_physical_interface(&name);
+            let link_speed = speeds
+                .get(&name)
+                .copied()
+                .unwrap_or(data.ifi_baudrate as u64);
+
+            samples.push(InterfaceSample {
+                name,
+                rx_bytes: data.ifi_ibytes as u64,
+                tx_bytes: data.ifi_obytes as u64,
+                rx_packets: data.ifi_ipackets as u64,
+                tx_packets: data.ifi_opackets as u64,
+                rx_errors: data.ifi_ierrors as u64,
+                tx_errors: data.ifi_oerrors as u64,
+                rx_drops: data.ifi_iqdrops as u64,
+                tx_drops: 0,
+                collisions: data.ifi_collisions as u64,
+                rx_multicast: data.ifi_imcasts as u64,
+                tx_multicast: data.ifi_omcasts as u64,
+                link_speed,
+                mtu: data.ifi_mtu,
+                wrap32: true,
+                mac: format_mac(mac),
+                addresses: Vec::new(),
+                flags,
+                is_loopback,
+                is_physical,
+            });
+        }
+
+        current = ifa.ifa_next;
+    }
+
+    unsafe { libc::freeifaddrs(addrs) };
+
+    for sample in &mut samples {
+        if let Some(found) = addresses.remove(&sample.name) {
+            sample.addresses = found;
+        }
+    }
+
+    Ok(samples)
+}
+
+pub fn load_interface_aliases() -> io::Result<HashMap<String, String>> {
+    let output = Command::new("networksetup")
+        .arg("-listallhardwareports")
+        .output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::new(io::ErrorKind::Other, "networksetup failed"));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut map = HashMap::new();
+    let mut current_port: Option<String> = None;
+
+    for line in stdout.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("Hardware Port:") {
+            current_port = Some(rest.trim().to_string());
+        } else if let Some(rest) = line.strip_prefix("Device:") {
+            let dev = rest.trim();
+            if !dev.is_empty() {
+                if let Some(port) = current_port.take() {
+                    map.insert(dev.to_string(), port);
+                }
+            }
+        }
+    }
+
+    Ok(map)
+}
@@ -0,0 +1,361 @@
+use std::fmt::Write as _;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::app::{App, IfaceState};
+use crate::model::Group;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_REQUEST_BYTES: usize = 8192;
+
+/// Serves the latest published metrics at `/metrics` in the Prometheus text
+/// exposition format from a background thread.
+pub struct Exporter {
+    body: Arc<Mutex<String>>,
+}
+
+impl Exporter {
+    pub fn start(addr: SocketAddr) -> io::Result<Self> {
+        Ok(Self::serve_on(TcpListener::bind(addr)?))
+    }
+
+    /// Serves on an already bound `listener`. Until the first publish,
+    /// scrapes get the metric descriptions with no samples.
+    fn serve_on(listener: TcpListener) -> Self {
+        let body = Arc::new(Mutex::new(render(None)));
+        let shared = Arc::clone(&body);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = serve(stream, &shared);
+            }
+        });
+        Self { body }
+    }
+
+    pub fn publish(&self, app: &App) {
+        let text = render(Some(app));
+        if let Ok(mut body) = self.body.lock() {
+            *body = text;
+        }
+    }
+}
+
+fn serve(mut stream: TcpStream, body: &Mutex<String>) -> io::Result<()> {
+    // Connections are served one at a time, so a client that stalls either
+    // way must not hold up the next scrape for long.
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut parts = request.lines().next().unwrap_or("").split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+
+    let (status, content) = if method != "GET" {
+        ("405 Method Not Allowed", String::new())
+    } else if path == "/metrics" {
+        let content = body.lock().map(|b| b.clone()).unwrap_or_default();
+        ("200 OK", content)
+    } else {
+        ("404 Not Found", String::new())
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{content}",
+        content.len()
+    )?;
+    stream.flush()
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn labels(app: &App, iface: &IfaceState) -> String {
+    let alias = app
+        .aliases
+        .get(&iface.name)
+        .map(String::as_str)
+        .unwrap_or("");
+    let group = match iface.group() {
+        Group::Physical => "physical",
+        Group::Virtual => "virtual",
+    };
+    format!(
+        "iface=\"{}\",alias=\"{}\",group=\"{}\"",
+        escape_label(&iface.name),
+        escape_label(alias),
+        group
+    )
+}
+
+type Metric = (
+    &'static str,
+    &'static str,
+    &'static str,
+    fn(&IfaceState) -> f64,
+);
+
+const IFACE_METRICS: [Metric; 17] = [
+    (
+        "macnetmon_rx_bytes_total",
+        "counter",
+        "Bytes received on the interface.",
+        |s| s.rx_bytes as f64,
+    ),
+    (
+        "macnetmon_tx_bytes_total",
+        "counter",
+        "Bytes transmitted on the interface.",
+        |s| s.tx_bytes as f64,
+    ),
+    (
+        "macnetmon_rx_packets_total",
+        "counter",
+        "Packets received on the interface.",
+        |s| s.rx_packets as f64,
+    ),
+    (
+        "macnetmon_tx_packets_total",
+        "counter",
+        "Packets transmitted on the interface.",
+        |s| s.tx_packets as f64,
+    ),
+    (
+        "macnetmon_rx_errors_total",
+        "counter",
+        "Receive errors on the interface.",
+        |s| s.rx_errors as f64,
+    ),
+    (
+        "macnetmon_tx_errors_total",
+        "counter",
+        "Transmit errors on the interface.",
+        |s| s.tx_errors as f64,
+    ),
+    (
+        "macnetmon_rx_drops_total",
+        "counter",
+        "Received packets dropped on the interface.",
+        |s| s.rx_drops as f64,
+    ),
+    (
+        "macnetmon_tx_drops_total",
+        "counter",
+        "Transmit packets dropped on the interface.",
+        |s| s.tx_drops as f64,
+    ),
+    (
+        "macnetmon_collisions_total",
+        "counter",
+        "Collisions seen on the interface.",
+        |s| s.collisions as f64,
+    ),
+    (
+        "macnetmon_counter_resets_total",
+        "counter",
+        "Times the interface counters went backwards and were treated as a reset.",
+        |s| s.counter_resets as f64,
+    ),
+    (
+        "macnetmon_rx_bytes_per_second",
+        "gauge",
+        "Receive rate over the last sample interval.",
+        |s| s.rx_rate,
+    ),
+    (
+        "macnetmon_tx_bytes_per_second",
+        "gauge",
+        "Transmit rate over the last sample interval.",
+        |s| s.tx_rate,
+    ),
+    (
+        "macnetmon_link_speed_bits_per_second",
+        "gauge",
+        "Nominal link speed, 0 when the driver does not report one.",
+        |s| s.link_speed as f64,
+    ),
+    (
+        "macnetmon_mtu_bytes",
+        "gauge",
+        "Maximum transmission unit of the interface.",
+        |s| s.mtu as f64,
+    ),
+    (
+        "macnetmon_interface_up",
+        "gauge",
+        "1 when the interface is up with a live link, 0 when down.",
+        |s| if s.up { 1.0 } else { 0.0 },
+    ),
+    (
+        "macnetmon_link_transitions_total",
+        "counter",
+        "Up/down changes since the interface was first seen.",
+        |s| s.transitions as f64,
+    ),
+    (
+        "macnetmon_interface_flags",
+        "gauge",
+        "Raw IFF_* flags of the interface.",
+        |s| s.flags as f64,
+    ),
+];
+
+/// The exposition for `app`'s latest sample, or with `None`, just the
+/// `HELP` and `TYPE` lines.
+fn render(app: Option<&App>) -> String {
+    let mut ifaces: Vec<(&IfaceState, String)> = match app {
+        Some(app) => app
+            .states
+            .values()
+            .map(|iface| (iface, labels(app, iface)))
+            .collect(),
+        None => Vec::new(),
+    };
+    ifaces.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+
+    let mut out = String::new();
+    for (name, kind, help, value) in IFACE_METRICS {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        for (iface, labels) in &ifaces {
+            let _ = writeln!(out, "{name}{{{labels}}} {}", value(iface));
+        }
+    }
+
+    let totals = [
+        (
+            "macnetmon_total_rx_bytes_per_second",
+            "Receive rate summed over all interfaces.",
+            app.map(|app| app.total_rx),
+        ),
+        (
+            "macnetmon_total_tx_bytes_per_second",
+            "Transmit rate summed over all interfaces.",
+            app.map(|app| app.total_tx),
+        ),
+    ];
+    for (name, help, value) in totals {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} gauge");
+        if let Some(value) = value {
+            let _ = writeln!(out, "{name} {value}");
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tests::{app, iface, RUNNING};
+
+    /// Sends `request` to a one-shot server on loopback and returns the reply.
+    fn scrape(request: &str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let body = Mutex::new("macnetmon_up 1\n".to_string());
+            let (stream, _) = listener.accept().unwrap();
+            serve(stream, &body).unwrap();
+        });
+
+        let mut client = TcpStream::connect(addr).unwrap();
+        client.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        server.join().unwrap();
+        response
+    }
+
+    #[test]
+    fn serves_metrics_over_loopback() {
+        let response = scrape("GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+        assert!(response.contains("Content-Length: 15\r\n"), "{response}");
+        assert!(response.ends_with("\r\n\r\nmacnetmon_up 1\n"), "{response}");
+    }
+
+    #[test]
+    fn rejects_other_paths_and_methods() {
+        let response = scrape("GET / HTTP/1.1\r\n\r\n");
+        assert!(
+            response.starts_with("HTTP/1.1 404 Not Found\r\n"),
+            "{response}"
+        );
+        let response = scrape("POST /metrics HTTP/1.1\r\n\r\n");
+        assert!(
+            response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"),
+            "{response}"
+        );
+    }
+
+    /// Body of a plain `GET /metrics` against `addr`.
+    fn get_metrics(addr: SocketAddr) -> String {
+        let mut client = TcpStream::connect(addr).unwrap();
+        client
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        body.to_string()
+    }
+
+    #[test]
+    fn exports_published_samples() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let exporter = Exporter::serve_on(listener);
+
+        // Before the first sample: valid exposition, just without samples.
+        let body = get_metrics(addr);
+        assert!(
+            body.starts_with("# HELP macnetmon_rx_bytes_total "),
+            "{body}"
+        );
+        assert!(body.contains("# TYPE macnetmon_rx_bytes_total counter\n"));
+        assert!(body.lines().all(|line| line.starts_with('#')), "{body}");
+
+        let mut app = app(vec![
+            vec![iface("en0", 1_000, 100, RUNNING)],
+            vec![iface("en0", 4_000, 600, RUNNING)],
+        ]);
+        app.update();
+        app.update();
+        app.aliases
+            .insert("en0".to_string(), "home \"wifi\"".to_string());
+        exporter.publish(&app);
+
+        let body = get_metrics(addr);
+        let samples: Vec<&str> = body
+            .lines()
+            .filter(|line| line.starts_with("macnetmon_rx_bytes_"))
+            .collect();
+        assert_eq!(
+            samples,
+            [
+                r#"macnetmon_rx_bytes_total{iface="en0",alias="home \"wifi\"",group="physical"} 4000"#,
+                r#"macnetmon_rx_bytes_per_second{iface="en0",alias="home \"wifi\"",group="physical"} 3000"#,
+            ]
+        );
+        assert!(body.contains("\nmacnetmon_total_rx_bytes_per_second 3000\n"));
+    }
+}
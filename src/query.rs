@@ -0,0 +1,487 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::alert::parse_duration;
+use crate::config::matches_any;
+use crate::events::local_time;
+use crate::store::{self, HistoryStore};
+use crate::ui::{format_bytes, format_rate};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum QueryFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+/// Parses a span like `90m`, `12h` or `7d` into whole seconds.
+pub fn parse_span(text: &str) -> Result<u64, String> {
+    let secs = parse_duration(text)?;
+    if secs < 1.0 {
+        return Err(format!("\"{text}\" is shorter than a second"));
+    }
+    Ok(secs as u64)
+}
+
+/// What to read from the saved history and how to print it.
+pub struct Query<'a> {
+    /// Interface globs to include; empty means all.
+    pub ifaces: &'a [String],
+    /// Interface globs to leave out.
+    pub exclude: &'a [String],
+    /// Seconds to look back from now.
+    pub since: u64,
+    /// Bucket width in seconds, or `None` for one row per interface.
+    pub agg: Option<u64>,
+    pub format: QueryFormat,
+}
+
+/// Rates and traffic of one interface over one bucket.
+#[derive(Clone, Debug, Serialize)]
+struct Row {
+    /// Start of the bucket in Unix seconds.
+    time: u64,
+    iface: String,
+    samples: u64,
+    /// Average byte rates over the time covered by samples.
+    rx_avg: f64,
+    rx_max: u64,
+    tx_avg: f64,
+    tx_max: u64,
+    rx_bytes: u64,
+    tx_bytes: u64,
+}
+
+#[derive(Default)]
+struct Acc {
+    samples: u64,
+    secs: f64,
+    rx_sum: f64,
+    tx_sum: f64,
+    rx_max: u64,
+    tx_max: u64,
+    rx_bytes: f64,
+    tx_bytes: f64,
+}
+
+impl Acc {
+    fn add(&mut self, rx: u64, tx: u64, secs: f64) {
+        self.samples += 1;
+        self.secs += secs;
+        self.rx_sum += rx as f64;
+        self.tx_sum += tx as f64;
+        self.rx_max = self.rx_max.max(rx);
+        self.tx_max = self.tx_max.max(tx);
+        self.rx_bytes += rx as f64 * secs;
+        self.tx_bytes += tx as f64 * secs;
+    }
+
+    fn merge(&mut self, other: &Acc) {
+        self.samples += other.samples;
+        self.secs += other.secs;
+        self.rx_sum += other.rx_sum;
+        self.tx_sum += other.tx_sum;
+        self.rx_max = self.rx_max.max(other.rx_max);
+        self.tx_max = self.tx_max.max(other.tx_max);
+        self.rx_bytes += other.rx_bytes;
+        self.tx_bytes += other.tx_bytes;
+    }
+
+    fn row(&self, time: u64, iface: &str) -> Row {
+        // Weight by time when durations are known; otherwise a plain mean.
+        let (rx_avg, tx_avg) = if self.secs > 0.0 {
+            (self.rx_bytes / self.secs, self.tx_bytes / self.secs)
+        } else {
+            let n = self.samples.max(1) as f64;
+            (self.rx_sum / n, self.tx_sum / n)
+        };
+        Row {
+            time,
+            iface: iface.to_string(),
+            samples: self.samples,
+            rx_avg,
+            rx_max: self.rx_max,
+            tx_avg,
+            tx_max: self.tx_max,
+            rx_bytes: self.rx_bytes as u64,
+            tx_bytes: self.tx_bytes as u64,
+        }
+    }
+}
+
+/// Reads the saved history and prints it aggregated as `query` asks.
+pub fn run(query: &Query<'_>) -> io::Result<()> {
+    let dir = HistoryStore::dir()
+        .filter(|dir| dir.is_dir())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "no saved history; set \"keep_history\": true in ~/.config/macnetmon.json to start one",
+            )
+        })?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (rows, totals) = aggregate(&dir, query, now)?;
+    if rows.is_empty() && query.format == QueryFormat::Table {
+        eprintln!("no saved samples in the last {}", span_label(query.since));
+        return Ok(());
+    }
+    match write(&mut io::stdout().lock(), query.format, &rows, &totals) {
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        other => other,
+    }
+}
+
+/// Buckets the history in `dir` from `query.since` seconds before `now` up
+/// to `now`. Returns the rows, then, when bucketing, one row per interface
+/// for the whole range.
+fn aggregate(dir: &Path, query: &Query<'_>, now: u64) -> io::Result<(Vec<Row>, Vec<Row>)> {
+    let start = now.saturating_sub(query.since);
+    let wanted = |name: &str| {
+        (query.ifaces.is_empty() || matches_any(query.ifaces, name))
+            && !matches_any(query.exclude, name)
+    };
+    let mut buckets: BTreeMap<(u64, String), Acc> = BTreeMap::new();
+    store::read_since(dir, start * 1_000_000, |names, tick| {
+        let secs = tick.dt as f64 / 1_000_000.0;
+        let time = tick.t / 1_000_000;
+        let bucket = match query.agg {
+            Some(width) => time - time % width,
+            None => start,
+        };
+        for (name, rates) in tick.ifaces(names) {
+            // A missing rate marks a counter reset: nothing was measured.
+            let (Some(rx), Some(tx)) = rates else {
+                continue;
+            };
+            if wanted(name) {
+                buckets
+                    .entry((bucket, name.to_string()))
+                    .or_default()
+                    .add(rx, tx, secs);
+            }
+        }
+    })?;
+    let rows = buckets
+        .iter()
+        .map(|((time, name), acc)| acc.row(*time, name))
+        .collect();
+
+    let mut totals: BTreeMap<&str, Acc> = BTreeMap::new();
+    if query.agg.is_some() {
+        for ((_, name), acc) in &buckets {
+            totals.entry(name).or_default().merge(acc);
+        }
+    }
+    let totals = totals
+        .iter()
+        .map(|(name, acc)| acc.row(start, name))
+        .collect();
+    Ok((rows, totals))
+}
+
+/// Prints `rows` in `format`. Only the table shows the per-interface
+/// `totals`; CSV and JSON stay one kind of row for other tools to read.
+fn write(
+    out: &mut impl Write,
+    format: QueryFormat,
+    rows: &[Row],
+    totals: &[Row],
+) -> io::Result<()> {
+    match format {
+        QueryFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, rows)?;
+            writeln!(out)
+        }
+        QueryFormat::Csv => write_csv(out, rows),
+        QueryFormat::Table => write_table(out, rows, totals),
+    }
+}
+
+fn write_csv(out: &mut impl Write, rows: &[Row]) -> io::Result<()> {
+    writeln!(
+        out,
+        "time,iface,samples,rx_avg,rx_max,tx_avg,tx_max,rx_bytes,tx_bytes"
+    )?;
+    for row in rows {
+        writeln!(
+            out,
+            "{},{},{},{:.0},{},{:.0},{},{},{}",
+            row.time,
+            row.iface,
+            row.samples,
+            row.rx_avg,
+            row.rx_max,
+            row.tx_avg,
+            row.tx_max,
+            row.rx_bytes,
+            row.tx_bytes
+        )?;
+    }
+    Ok(())
+}
+
+fn write_table(out: &mut impl Write, rows: &[Row], totals: &[Row]) -> io::Result<()> {
+    let header = [
+        "Time",
+        "Interface",
+        "RX avg",
+        "RX max",
+        "TX avg",
+        "TX max",
+        "RX",
+        "TX",
+    ];
+    let cells = |row: &Row, time: String| {
+        [
+            time,
+            row.iface.clone(),
+            format_rate(row.rx_avg, false).trim().to_string(),
+            format_rate(row.rx_max as f64, false).trim().to_string(),
+            format_rate(row.tx_avg, false).trim().to_string(),
+            format_rate(row.tx_max as f64, false).trim().to_string(),
+            format_bytes(row.rx_bytes),
+            format_bytes(row.tx_bytes),
+        ]
+    };
+    let mut lines: Vec<Option<[String; 8]>> = rows
+        .iter()
+        .map(|row| Some(cells(row, clock(row.time))))
+        .collect();
+    if !totals.is_empty() {
+        lines.push(None);
+        lines.extend(
+            totals
+                .iter()
+                .map(|row| Some(cells(row, "total".to_string()))),
+        );
+    }
+
+    let mut widths = header.map(str::len);
+    for line in lines.iter().flatten() {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let format_line = |line: &[String]| {
+        line.iter()
+            .zip(widths)
+            .enumerate()
+            .map(|(idx, (cell, width))| {
+                // Text columns line up left, numbers right.
+                if idx < 2 {
+                    format!("{cell:<width$}")
+                } else {
+                    format!("{cell:>width$}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+
+    writeln!(out, "{}", format_line(&header.map(String::from)))?;
+    for line in &lines {
+        match line {
+            Some(line) => writeln!(out, "{}", format_line(line))?,
+            None => writeln!(out)?,
+        }
+    }
+    Ok(())
+}
+
+/// Local `YYYY-MM-DD HH:MM` for Unix seconds `secs`.
+fn clock(secs: u64) -> String {
+    match local_time(UNIX_EPOCH + Duration::from_secs(secs)) {
+        Some(tm) => format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}",
+            tm.tm_year + 1900,
+            tm.tm_mon + 1,
+            tm.tm_mday,
+            tm.tm_hour,
+            tm.tm_min
+        ),
+        None => secs.to_string(),
+    }
+}
+
+/// `secs` in the largest unit that divides it, e.g. `2h` or `90m`.
+fn span_label(secs: u64) -> String {
+    [(86400, "d"), (3600, "h"), (60, "m")]
+        .iter()
+        .find(|(unit, _)| secs % unit == 0)
+        .map(|(unit, suffix)| format!("{}{suffix}", secs / unit))
+        .unwrap_or_else(|| format!("{secs}s"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("macnetmon-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Two minutes of ticks 10s apart, ending at the returned time: en0 at
+    /// 100 B/s RX then 300 B/s, with one counter reset in the second minute,
+    /// and a steady lo0.
+    fn two_minutes(dir: &Path) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            / 60
+            * 60;
+        let mut store = HistoryStore::open_in(dir.to_path_buf(), 7).unwrap();
+        for step in 0..12 {
+            let t = now - 120 + step * 10;
+            let rx = if step < 6 { 100 } else { 300 };
+            let en0 = if step == 8 {
+                (None, None)
+            } else {
+                (Some(rx), Some(50))
+            };
+            let rates = BTreeMap::from([
+                ("en0".to_string(), en0),
+                ("lo0".to_string(), (Some(7), Some(7))),
+            ]);
+            store
+                .append(t * 1_000_000, 10_000_000, (rx, 50), &rates)
+                .unwrap();
+        }
+        now
+    }
+
+    fn query(agg: Option<u64>, exclude: &[String]) -> Query<'_> {
+        Query {
+            ifaces: &[],
+            exclude,
+            since: 120,
+            agg,
+            format: QueryFormat::Table,
+        }
+    }
+
+    fn output(format: QueryFormat, rows: &[Row], totals: &[Row]) -> String {
+        let mut out = Vec::new();
+        write(&mut out, format, rows, totals).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn spans_take_units() {
+        assert_eq!(parse_span("90s"), Ok(90));
+        assert_eq!(parse_span("90m"), Ok(90 * 60));
+        assert_eq!(parse_span("2h"), Ok(2 * 60 * 60));
+        assert_eq!(parse_span("1d"), Ok(24 * 60 * 60));
+        assert_eq!(parse_span("300"), Ok(300));
+        for text in ["", "h", "2w", "1.5x", "0.5s", "-1h"] {
+            assert!(parse_span(text).is_err(), "accepted {text:?}");
+        }
+    }
+
+    #[test]
+    fn buckets_average_over_measured_time() {
+        let dir = temp_dir("query-buckets");
+        let now = two_minutes(&dir);
+        let exclude = ["lo*".to_string()];
+        let (rows, totals) = aggregate(&dir, &query(Some(60), &exclude), now).unwrap();
+
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|row| {
+                (
+                    row.time,
+                    row.iface.as_str(),
+                    row.samples,
+                    row.rx_avg,
+                    row.rx_bytes,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (now - 120, "en0", 6, 100.0, 6000),
+                (now - 60, "en0", 5, 300.0, 15000),
+            ]
+        );
+        assert_eq!(totals.len(), 1);
+        let total = &totals[0];
+        assert_eq!(
+            (total.time, total.samples, total.rx_max),
+            (now - 120, 11, 300)
+        );
+        assert_eq!((total.rx_bytes, total.tx_bytes), (21000, 5500));
+        assert_eq!(total.rx_avg, 21000.0 / 110.0);
+
+        // Without buckets: one row per interface, and no separate totals.
+        let (rows, totals) = aggregate(&dir, &query(None, &[]), now).unwrap();
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|row| (row.iface.as_str(), row.samples, row.rx_bytes))
+            .collect();
+        assert_eq!(summary, [("en0", 11, 21000), ("lo0", 12, 840)]);
+        assert!(totals.is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn writes_csv_json_and_table_rows() {
+        let dir = temp_dir("query-output");
+        let now = two_minutes(&dir);
+        let exclude = ["lo*".to_string()];
+        let (rows, totals) = aggregate(&dir, &query(Some(60), &exclude), now).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            output(QueryFormat::Csv, &rows, &totals),
+            format!(
+                "time,iface,samples,rx_avg,rx_max,tx_avg,tx_max,rx_bytes,tx_bytes\n\
+                 {},en0,6,100,100,50,50,6000,3000\n\
+                 {},en0,5,300,300,50,50,15000,2500\n",
+                now - 120,
+                now - 60
+            )
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&output(QueryFormat::Json, &rows, &totals)).unwrap();
+        let json = json.as_array().unwrap();
+        assert_eq!(json.len(), 2);
+        // Read back through serde_json's map, which sorts the keys.
+        let keys: Vec<&str> = json[0]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut expected = [
+            "time", "iface", "samples", "rx_avg", "rx_max", "tx_avg", "tx_max", "rx_bytes",
+            "tx_bytes",
+        ];
+        expected.sort_unstable();
+        assert_eq!(keys, expected);
+        assert_eq!(json[1]["time"], now - 60);
+        assert_eq!(json[1]["rx_bytes"], 15000);
+
+        let table = output(QueryFormat::Table, &rows, &totals);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 5, "{table}");
+        assert!(lines[0].starts_with("Time"));
+        assert_eq!(lines[3], "");
+        assert!(lines[4].starts_with("total"), "{table}");
+        assert!(lines[4].contains("en0"), "{table}");
+    }
+}
@@ -0,0 +1,491 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::config_path;
+use crate::constants::{HOUR_ROLLUPS, MINUTE_ROLLUPS};
+
+const ROLLUP_FILE: &str = "macnetmon-rollups.jsonl";
+
+/// Count, extremes and sum of the rates that landed in one bucket: enough to
+/// merge buckets later without losing the peaks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stats {
+    pub count: u32,
+    pub min: u64,
+    pub max: u64,
+    pub sum: u64,
+}
+
+impl Stats {
+    fn add(&mut self, value: u64) {
+        self.merge(&Stats {
+            count: 1,
+            min: value,
+            max: value,
+            sum: value,
+        });
+    }
+
+    pub fn merge(&mut self, other: &Stats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.sum = self.sum.saturating_add(other.sum);
+    }
+
+    pub fn avg(&self) -> Option<u64> {
+        (self.count > 0).then(|| self.sum / self.count as u64)
+    }
+}
+
+/// Rates seen during one fixed slice of wall-clock time.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Bucket {
+    /// Unix seconds, aligned to the tier width.
+    pub start: u64,
+    pub rx: Stats,
+    pub tx: Stats,
+}
+
+/// Buckets of one width, newest first, capped at `capacity`. The newest
+/// bucket is still open while samples keep landing in it.
+#[derive(Clone, Debug)]
+pub struct Tier {
+    pub width: u64,
+    capacity: usize,
+    buckets: VecDeque<Bucket>,
+}
+
+impl Tier {
+    fn new(width: u64, capacity: usize) -> Self {
+        Self {
+            width,
+            capacity,
+            buckets: VecDeque::new(),
+        }
+    }
+
+    /// Adds one sample taken at `time` (Unix seconds). Returns the bucket it
+    /// closed, if the sample started a new one. Samples older than the open
+    /// bucket, e.g. after the clock stepped back, are dropped.
+    fn add(&mut self, time: u64, rx: u64, tx: u64) -> Option<Bucket> {
+        let start = time - time % self.width;
+        let mut closed = None;
+        match self.buckets.front() {
+            Some(open) if open.start > start => return None,
+            Some(open) if open.start == start => {}
+            open => {
+                closed = open.copied();
+                self.buckets.push_front(Bucket {
+                    start,
+                    rx: Stats::default(),
+                    tx: Stats::default(),
+                });
+                self.buckets.truncate(self.capacity);
+            }
+        }
+        if let Some(open) = self.buckets.front_mut() {
+            open.rx.add(rx);
+            open.tx.add(tx);
+        }
+        closed
+    }
+
+    /// Files a bucket read back from disk. A bucket is written again each
+    /// time it grows (at exit while open, and once it closes), so a later
+    /// record for the same slice replaces the earlier one.
+    fn insert(&mut self, bucket: Bucket) {
+        let pos = self.buckets.iter().position(|b| b.start <= bucket.start);
+        match pos {
+            Some(idx) if self.buckets[idx].start == bucket.start => self.buckets[idx] = bucket,
+            Some(idx) => self.buckets.insert(idx, bucket),
+            None => self.buckets.push_back(bucket),
+        }
+        self.buckets.truncate(self.capacity);
+    }
+
+    /// Buckets covering the `span` seconds up to and including `now`.
+    fn window(&self, now: u64, span: u64) -> impl Iterator<Item = &Bucket> {
+        let newest = now - now % self.width;
+        let oldest = (newest + self.width).saturating_sub(span);
+        self.buckets
+            .iter()
+            .filter(move |b| b.start >= oldest && b.start <= newest)
+    }
+
+    /// Average RX and TX rates over the last `span` seconds, newest first, one
+    /// column per `column_secs` (rounded down to whole buckets, at least one).
+    /// Slices with no samples, such as while the link was down or macnetmon
+    /// wasn't running, come back as `None`, except before the oldest bucket,
+    /// where the series just ends.
+    pub fn series(
+        &self,
+        now: u64,
+        span: u64,
+        column_secs: u64,
+    ) -> (Vec<Option<u64>>, Vec<Option<u64>>) {
+        let column_secs = (column_secs / self.width).max(1) * self.width;
+        let len = ((span + column_secs - 1) / column_secs) as usize;
+        let newest = now - now % self.width;
+        let mut merged = vec![(Stats::default(), Stats::default()); len];
+        for bucket in self.window(now, span) {
+            let column = ((newest - bucket.start) / column_secs) as usize;
+            if let Some((rx, tx)) = merged.get_mut(column) {
+                rx.merge(&bucket.rx);
+                tx.merge(&bucket.tx);
+            }
+        }
+        while merged.last().is_some_and(|(rx, _)| rx.count == 0) {
+            merged.pop();
+        }
+        merged.iter().map(|(rx, tx)| (rx.avg(), tx.avg())).unzip()
+    }
+
+    /// RX and TX stats merged over the last `span` seconds.
+    pub fn summary(&self, now: u64, span: u64) -> (Stats, Stats) {
+        let mut rx = Stats::default();
+        let mut tx = Stats::default();
+        for bucket in self.window(now, span) {
+            rx.merge(&bucket.rx);
+            tx.merge(&bucket.tx);
+        }
+        (rx, tx)
+    }
+}
+
+/// One-minute and one-hour rollups of an interface's byte rates, kept well
+/// past what the raw history holds.
+#[derive(Clone, Debug)]
+pub struct Rollups {
+    pub minute: Tier,
+    pub hour: Tier,
+}
+
+impl Default for Rollups {
+    fn default() -> Self {
+        Self {
+            minute: Tier::new(60, MINUTE_ROLLUPS),
+            hour: Tier::new(60 * 60, HOUR_ROLLUPS),
+        }
+    }
+}
+
+impl Rollups {
+    /// Adds one sample to every tier, returning the buckets that closed
+    /// along with their tier width.
+    pub fn add(&mut self, time: u64, rx: u64, tx: u64) -> Vec<(u64, Bucket)> {
+        [&mut self.minute, &mut self.hour]
+            .into_iter()
+            .filter_map(|tier| Some((tier.width, tier.add(time, rx, tx)?)))
+            .collect()
+    }
+
+    /// Whether every bucket is older than the hour tier reaches back from
+    /// `now`, as happens once an interface has been gone for that long.
+    fn is_stale(&self, now: u64) -> bool {
+        let span = self.hour.width * self.hour.capacity as u64;
+        match self.hour.buckets.front() {
+            Some(newest) => newest.start + span <= now,
+            None => self.minute.buckets.is_empty(),
+        }
+    }
+
+    fn tier_mut(&mut self, width: u64) -> Option<&mut Tier> {
+        [&mut self.minute, &mut self.hour]
+            .into_iter()
+            .find(|tier| tier.width == width)
+    }
+
+    fn open_buckets(&self) -> impl Iterator<Item = (u64, &Bucket)> {
+        [&self.minute, &self.hour]
+            .into_iter()
+            .filter_map(|tier| Some((tier.width, tier.buckets.front()?)))
+    }
+}
+
+/// Forgets interfaces whose rollups have all aged out as of `now` (Unix
+/// seconds), so ones seen once long ago don't pile up in memory or on disk.
+pub fn prune(ifaces: &mut HashMap<String, Rollups>, now: u64) {
+    ifaces.retain(|_, rollups| !rollups.is_stale(now));
+}
+
+/// One line of the rollup file.
+#[derive(Serialize, Deserialize)]
+struct Record<'a> {
+    /// Interface name, or absent for the all-interfaces total.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    iface: Option<std::borrow::Cow<'a, str>>,
+    /// Tier width in seconds.
+    width: u64,
+    #[serde(flatten)]
+    bucket: Bucket,
+}
+
+/// Append-only JSON Lines file of closed rollup buckets, so the long windows
+/// survive a restart. It's rewritten with just the retained buckets on
+/// startup to keep it from growing without bound.
+pub struct RollupFile {
+    out: LineWriter<File>,
+}
+
+impl RollupFile {
+    /// Reads back everything in the file into `ifaces` and `total`, then
+    /// compacts it, minus interfaces that have aged out, and opens it for
+    /// appending.
+    pub fn open(ifaces: &mut HashMap<String, Rollups>, total: &mut Rollups) -> io::Result<Self> {
+        let path = config_path(ROLLUP_FILE)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self::open_at(&path, ifaces, total, now)
+    }
+
+    fn open_at(
+        path: &Path,
+        ifaces: &mut HashMap<String, Rollups>,
+        total: &mut Rollups,
+        now: u64,
+    ) -> io::Result<Self> {
+        if let Ok(file) = File::open(path) {
+            for line in BufReader::new(file).lines() {
+                let Ok(record) = serde_json::from_str::<Record<'_>>(&line?) else {
+                    continue;
+                };
+                let rollups = match record.iface {
+                    Some(name) => ifaces.entry(name.into_owned()).or_default(),
+                    None => &mut *total,
+                };
+                if let Some(tier) = rollups.tier_mut(record.width) {
+                    tier.insert(record.bucket);
+                }
+            }
+        }
+        prune(ifaces, now);
+
+        let tmp = path.with_extension("jsonl.tmp");
+        {
+            let mut out = io::BufWriter::new(File::create(&tmp)?);
+            let all = ifaces
+                .iter()
+                .map(|(name, rollups)| (Some(name.as_str()), rollups))
+                .chain([(None, &*total)]);
+            for (name, rollups) in all {
+                for tier in [&rollups.minute, &rollups.hour] {
+                    for bucket in tier.buckets.iter().rev() {
+                        write_record(&mut out, name, tier.width, bucket)?;
+                    }
+                }
+            }
+            out.flush()?;
+        }
+        fs::rename(&tmp, path)?;
+
+        let file = OpenOptions::new().append(true).open(path)?;
+        Ok(Self {
+            out: LineWriter::new(file),
+        })
+    }
+
+    pub fn append(&mut self, iface: Option<&str>, width: u64, bucket: &Bucket) -> io::Result<()> {
+        write_record(&mut self.out, iface, width, bucket)
+    }
+
+    /// Writes the still-open buckets so a restart within the same minute or
+    /// hour picks up where this run left off.
+    pub fn flush_open(&mut self, ifaces: &HashMap<String, Rollups>, total: &Rollups) {
+        let all = ifaces
+            .iter()
+            .map(|(name, rollups)| (Some(name.as_str()), rollups))
+            .chain([(None, total)]);
+        for (name, rollups) in all {
+            for (width, bucket) in rollups.open_buckets() {
+                if self.append(name, width, bucket).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn write_record(
+    out: &mut impl Write,
+    iface: Option<&str>,
+    width: u64,
+    bucket: &Bucket,
+) -> io::Result<()> {
+    let record = Record {
+        iface: iface.map(Into::into),
+        width,
+        bucket: *bucket,
+    };
+    serde_json::to_writer(&mut *out, &record)?;
+    out.write_all(b"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// A minute on an hour boundary, so tests line up with both tiers.
+    const NOW: u64 = 1_700_000_000 / 3600 * 3600 + 59 * 60;
+
+    fn temp_file(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("macnetmon-{name}-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    /// One sample a minute over the hour up to `NOW`, oldest first, with RX
+    /// set to how many minutes before `NOW` it was taken and TX twice that.
+    /// Minutes 15 to 29 before `NOW` are left out.
+    fn hour_with_gap() -> Rollups {
+        let mut rollups = Rollups::default();
+        for ago in (0..60).rev().filter(|ago| !(15..30).contains(ago)) {
+            rollups.add(NOW - ago * 60, ago, ago * 2);
+        }
+        rollups
+    }
+
+    #[test]
+    fn buckets_close_when_a_sample_crosses_the_boundary() {
+        let mut tier = Tier::new(60, 10);
+        assert!(tier.add(120, 1, 1).is_none());
+        assert!(tier.add(179, 3, 3).is_none());
+        let closed = tier.add(180, 5, 5).unwrap();
+        assert_eq!(closed.start, 120);
+        assert_eq!(closed.rx.count, 2);
+        // Late samples for a closed bucket are dropped.
+        assert!(tier.add(150, 7, 7).is_none());
+        assert_eq!(tier.buckets.len(), 2);
+
+        let mut rollups = Rollups::default();
+        assert!(rollups.add(3599, 1, 1).is_empty());
+        let widths: Vec<u64> = rollups.add(3600, 1, 1).iter().map(|(w, _)| *w).collect();
+        assert_eq!(widths, [60, 3600]);
+        let widths: Vec<u64> = rollups.add(3660, 1, 1).iter().map(|(w, _)| *w).collect();
+        assert_eq!(widths, [60]);
+    }
+
+    #[test]
+    fn buckets_keep_min_avg_and_max() {
+        let mut tier = Tier::new(60, 10);
+        for rx in [10, 30, 20] {
+            tier.add(60, rx, 0);
+        }
+        let rx = tier.buckets[0].rx;
+        assert_eq!((rx.count, rx.min, rx.max, rx.avg()), (3, 10, 30, Some(20)));
+        assert_eq!(Stats::default().avg(), None);
+
+        let mut merged = Stats::default();
+        merged.merge(&rx);
+        merged.merge(&Stats {
+            count: 1,
+            min: 5,
+            max: 5,
+            sum: 5,
+        });
+        assert_eq!((merged.min, merged.max, merged.avg()), (5, 30, Some(16)));
+    }
+
+    #[test]
+    fn series_cover_the_window_and_show_gaps() {
+        let rollups = hour_with_gap();
+
+        // The 1h window: one column per minute.
+        let (rx, tx) = rollups.minute.series(NOW, 60 * 60, 60);
+        assert_eq!(rx.len(), 60);
+        for (ago, (rx, tx)) in rx.iter().zip(&tx).enumerate() {
+            let ago = ago as u64;
+            if (15..30).contains(&ago) {
+                assert_eq!((*rx, *tx), (None, None), "{ago} minutes ago");
+            } else {
+                assert_eq!((*rx, *tx), (Some(ago), Some(ago * 2)), "{ago} minutes ago");
+            }
+        }
+
+        // The 24h window: quarter-hour columns, ending at the oldest bucket
+        // instead of padding out to a full day.
+        let (rx, tx) = rollups.minute.series(NOW, 24 * 60 * 60, 15 * 60);
+        assert_eq!(rx, [Some(7), None, Some(37), Some(52)]);
+        assert_eq!(tx, [Some(14), None, Some(74), Some(104)]);
+
+        // Columns narrower than a bucket still take a whole one.
+        let (rx, _) = rollups.hour.series(NOW, 24 * 60 * 60, 60);
+        assert_eq!(rx.len(), 1);
+    }
+
+    #[test]
+    fn summary_covers_only_the_span() {
+        let rollups = hour_with_gap();
+        let (rx, tx) = rollups.minute.summary(NOW, 10 * 60);
+        assert_eq!((rx.count, rx.min, rx.max, rx.sum), (10, 0, 9, 45));
+        assert_eq!((tx.min, tx.max, tx.avg()), (0, 18, Some(9)));
+
+        // Reaching into the gap adds nothing for it.
+        let (rx, _) = rollups.minute.summary(NOW, 20 * 60);
+        assert_eq!((rx.count, rx.max), (15, 14));
+    }
+
+    #[test]
+    fn rollups_survive_a_restart() {
+        let path = temp_file("rollups");
+        let mut ifaces = HashMap::new();
+        let mut total = Rollups::default();
+        let mut file = RollupFile::open_at(&path, &mut ifaces, &mut total, NOW).unwrap();
+
+        // Long gone: nothing newer than the hour tier reaches back.
+        let gone_at = NOW - 60 * 60 * HOUR_ROLLUPS as u64;
+        for time in [gone_at - 60, gone_at] {
+            for (width, bucket) in ifaces.entry("en9".to_string()).or_default().add(time, 1, 1) {
+                file.append(Some("en9"), width, &bucket).unwrap();
+            }
+        }
+        let mut en0 = Rollups::default();
+        for ago in (0..60).rev() {
+            for (width, bucket) in en0.add(NOW - ago * 60, ago, ago * 2) {
+                file.append(Some("en0"), width, &bucket).unwrap();
+            }
+            for (width, bucket) in total.add(NOW - ago * 60, ago * 3, ago) {
+                file.append(None, width, &bucket).unwrap();
+            }
+        }
+        ifaces.insert("en0".to_string(), en0);
+        file.flush_open(&ifaces, &total);
+        drop(file);
+
+        let mut loaded = HashMap::new();
+        let mut loaded_total = Rollups::default();
+        RollupFile::open_at(&path, &mut loaded, &mut loaded_total, NOW).unwrap();
+        let mut names: Vec<&String> = loaded.keys().collect();
+        names.sort();
+        assert_eq!(names, ["en0"]);
+        for (before, after) in [(&ifaces["en0"], &loaded["en0"]), (&total, &loaded_total)] {
+            for (a, b) in [(&before.minute, &after.minute), (&before.hour, &after.hour)] {
+                assert_eq!(a.series(NOW, 60 * 60, 60), b.series(NOW, 60 * 60, 60));
+                assert_eq!(a.summary(NOW, 60 * 60), b.summary(NOW, 60 * 60));
+            }
+        }
+
+        // Compacted on open: one line per retained bucket.
+        let lines = fs::read_to_string(&path).unwrap().lines().count();
+        assert_eq!(lines, 2 * (60 + 1));
+        let _ = fs::remove_file(&path);
+    }
+}
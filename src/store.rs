@@ -0,0 +1,398 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, LineWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::config_path;
+use crate::events::local_time;
+
+const HISTORY_DIR: &str = "macnetmon-history";
+const SEGMENT_EXT: &str = "jsonl";
+
+/// RX and TX byte rates of one interface; `None` marks a counter reset.
+pub type Rates = (Option<u64>, Option<u64>);
+
+/// First line of every segment: the interfaces its ticks have rates for, so
+/// the names are written once rather than on every line.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Header {
+    ifaces: Vec<String>,
+}
+
+/// One sample as stored on disk: the byte rates of every interface that was
+/// up, plus the all-interfaces total.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Tick {
+    /// Wall-clock time in Unix microseconds.
+    pub t: u64,
+    /// Microseconds the rates were measured over.
+    pub dt: u64,
+    pub total: (u64, u64),
+    /// Rates in the order of the segment's header, `None` for interfaces
+    /// that weren't up.
+    pub rates: Vec<Option<Rates>>,
+}
+
+impl Tick {
+    /// Rates of the interfaces that were up, by name.
+    pub fn ifaces<'a>(&'a self, names: &'a [String]) -> impl Iterator<Item = (&'a str, Rates)> {
+        names
+            .iter()
+            .zip(&self.rates)
+            .filter_map(|(name, rates)| Some((name.as_str(), (*rates)?)))
+    }
+}
+
+/// Local date of `time_us` as `YYYY-MM-DD`, which is also the name of the
+/// segment it belongs in.
+fn segment_day(time_us: u64) -> String {
+    match local_time(UNIX_EPOCH + Duration::from_micros(time_us)) {
+        Some(tm) => format!(
+            "{:04}-{:02}-{:02}",
+            tm.tm_year + 1900,
+            tm.tm_mon + 1,
+            tm.tm_mday
+        ),
+        None => "unknown".to_string(),
+    }
+}
+
+/// Append-only history on disk in JSON Lines segments. Each segment is a
+/// `Header` followed by one `Tick` per line; a new one starts each local day
+/// and whenever an interface shows up that the current header doesn't list.
+/// Segments from days older than the retention period are deleted.
+pub struct HistoryStore {
+    dir: PathBuf,
+    retention_days: u64,
+    day: String,
+    header: Header,
+    out: Option<LineWriter<File>>,
+}
+
+impl HistoryStore {
+    pub fn dir() -> Option<PathBuf> {
+        config_path(HISTORY_DIR)
+    }
+
+    pub fn open(retention_days: u64) -> io::Result<Self> {
+        let dir = Self::dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))?;
+        Self::open_in(dir, retention_days)
+    }
+
+    pub fn open_in(dir: PathBuf, retention_days: u64) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            retention_days: retention_days.max(1),
+            day: String::new(),
+            header: Header::default(),
+            out: None,
+        })
+    }
+
+    /// Oldest time, in Unix microseconds, that's still within retention at
+    /// `now_us`.
+    pub fn cutoff_us(&self, now_us: u64) -> u64 {
+        now_us.saturating_sub(self.retention_days * 24 * 60 * 60 * 1_000_000)
+    }
+
+    /// Writes one tick taken at `t` over `dt` microseconds.
+    pub fn append(
+        &mut self,
+        t: u64,
+        dt: u64,
+        total: (u64, u64),
+        ifaces: &BTreeMap<String, Rates>,
+    ) -> io::Result<()> {
+        let day = segment_day(t);
+        let known = |name: &String| self.header.ifaces.contains(name);
+        if self.out.is_none() || day != self.day || !ifaces.keys().all(known) {
+            // Within a day the header only grows, so an interface that comes
+            // and goes doesn't start a segment each time.
+            if day != self.day {
+                self.header.ifaces.clear();
+            }
+            for name in ifaces.keys() {
+                if !self.header.ifaces.contains(name) {
+                    self.header.ifaces.push(name.clone());
+                }
+            }
+            // Named for the first tick, so segments sort by time.
+            let path = self.dir.join(format!("{day}.{t}.{SEGMENT_EXT}"));
+            let file = OpenOptions::new().write(true).create_new(true).open(path)?;
+            let mut out = LineWriter::new(file);
+            serde_json::to_writer(&mut out, &self.header)?;
+            out.write_all(b"\n")?;
+            self.out = Some(out);
+            self.day = day;
+            self.prune(t);
+        }
+        if let Some(out) = &mut self.out {
+            let tick = Tick {
+                t,
+                dt,
+                total,
+                rates: self
+                    .header
+                    .ifaces
+                    .iter()
+                    .map(|name| ifaces.get(name).copied())
+                    .collect(),
+            };
+            serde_json::to_writer(&mut *out, &tick)?;
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// Deletes segments for days that have fallen out of retention.
+    fn prune(&self, now_us: u64) {
+        let oldest = segment_day(self.cutoff_us(now_us));
+        for path in segments(&self.dir) {
+            if path_day(&path) < oldest.as_str() {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// Segment files in `dir`, oldest first.
+fn segments(dir: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == SEGMENT_EXT))
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+/// The `YYYY-MM-DD` a segment's name starts with.
+fn path_day(path: &Path) -> &str {
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("");
+    stem.split('.').next().unwrap_or("")
+}
+
+/// Reads a segment's header, leaving `reader` at the first tick. Files
+/// without one, such as segments from before headers were written, read as
+/// `None`.
+fn read_header(reader: &mut impl BufRead) -> io::Result<Option<Vec<String>>> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    Ok(serde_json::from_str::<Header>(&line)
+        .ok()
+        .map(|header| header.ifaces))
+}
+
+/// Calls `visit` with every tick in `dir` taken at or after `since_us`,
+/// oldest first, along with the names its rates belong to. Lines that don't
+/// parse, such as one cut short by a crash, are skipped.
+pub fn read_since(
+    dir: &Path,
+    since_us: u64,
+    mut visit: impl FnMut(&[String], &Tick),
+) -> io::Result<()> {
+    let first_day = segment_day(since_us);
+    for path in segments(dir) {
+        if path_day(&path) < first_day.as_str() {
+            continue;
+        }
+        let mut reader = BufReader::new(File::open(&path)?);
+        let Some(names) = read_header(&mut reader)? else {
+            continue;
+        };
+        for line in reader.lines() {
+            if let Ok(tick) = serde_json::from_str::<Tick>(&line?) {
+                if tick.t >= since_us {
+                    visit(&names, &tick);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Bytes read at a time when walking a segment backwards.
+const TAIL_CHUNK: u64 = 64 * 1024;
+
+/// Up to `count` of the newest ticks in `dir` taken at or after `since_us`,
+/// newest first, each with the names its rates belong to. Only the ends of
+/// the newest segments are read.
+pub fn read_newest(
+    dir: &Path,
+    since_us: u64,
+    count: usize,
+) -> io::Result<Vec<(Rc<[String]>, Tick)>> {
+    let mut ticks = Vec::new();
+    'segments: for path in segments(dir).iter().rev() {
+        let mut file = File::open(path)?;
+        let Some(names) = read_header(&mut BufReader::new(&file))? else {
+            continue;
+        };
+        let names: Rc<[String]> = names.into();
+
+        // Walk back a chunk at a time. A chunk's first line may start in the
+        // chunk before, so it's carried over and finished on the next pass.
+        let mut end = file.seek(SeekFrom::End(0))?;
+        let mut carry = Vec::new();
+        while end > 0 {
+            let start = end.saturating_sub(TAIL_CHUNK);
+            let mut chunk = vec![0; (end - start) as usize];
+            file.seek(SeekFrom::Start(start))?;
+            file.read_exact(&mut chunk)?;
+            chunk.extend_from_slice(&carry);
+            let whole = match chunk.iter().position(|b| *b == b'\n') {
+                _ if start == 0 => 0,
+                Some(idx) => idx + 1,
+                None => chunk.len(),
+            };
+            for line in chunk[whole..].split(|b| *b == b'\n').rev() {
+                let Ok(tick) = serde_json::from_slice::<Tick>(line) else {
+                    continue;
+                };
+                if tick.t < since_us || ticks.len() >= count {
+                    break 'segments;
+                }
+                ticks.push((Rc::clone(&names), tick));
+            }
+            chunk.truncate(whole);
+            carry = chunk;
+            end = start;
+        }
+    }
+    Ok(ticks)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("macnetmon-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn rates(entries: &[(&str, u64)]) -> BTreeMap<String, Rates> {
+        entries
+            .iter()
+            .map(|(name, rx)| (name.to_string(), (Some(*rx), Some(rx / 2))))
+            .collect()
+    }
+
+    #[test]
+    fn names_are_written_once_per_segment() {
+        let dir = temp_dir("segments");
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as u64;
+        let mut store = HistoryStore::open_in(dir.clone(), 7).unwrap();
+        store
+            .append(now, 1_000_000, (10, 5), &rates(&[("en0", 10)]))
+            .unwrap();
+        store
+            .append(now + 1, 1_000_000, (0, 0), &BTreeMap::new())
+            .unwrap();
+        // A new interface starts a segment whose header keeps the old ones.
+        store
+            .append(
+                now + 2,
+                1_000_000,
+                (30, 15),
+                &rates(&[("en1", 20), ("en0", 10)]),
+            )
+            .unwrap();
+        store
+            .append(now + 3, 1_000_000, (20, 10), &rates(&[("en1", 20)]))
+            .unwrap();
+
+        let files = segments(&dir);
+        assert_eq!(files.len(), 2);
+        let text = fs::read_to_string(&files[1]).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some(r#"{"ifaces":["en0","en1"]}"#));
+        assert!(lines.all(|line| !line.contains("en0")));
+
+        let mut read = Vec::new();
+        read_since(&dir, now + 1, |names, tick| {
+            let ifaces: Vec<(String, Rates)> = tick
+                .ifaces(names)
+                .map(|(name, rates)| (name.to_string(), rates))
+                .collect();
+            read.push((tick.t, ifaces));
+        })
+        .unwrap();
+        let rates = |rx: u64| (Some(rx), Some(rx / 2));
+        assert_eq!(
+            read,
+            [
+                (now + 1, vec![]),
+                (
+                    now + 2,
+                    vec![
+                        ("en0".to_string(), rates(10)),
+                        ("en1".to_string(), rates(20))
+                    ]
+                ),
+                (now + 3, vec![("en1".to_string(), rates(20))]),
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn newest_ticks_come_from_the_tail() {
+        let dir = temp_dir("tail");
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as u64;
+        let mut store = HistoryStore::open_in(dir.clone(), 7).unwrap();
+        // Enough lines that the tail spans several chunks.
+        let count = 5_000u64;
+        for idx in 0..count {
+            let t = now - (count - idx) * 1_000_000;
+            store
+                .append(t, 1_000_000, (idx, idx), &rates(&[("en0", idx)]))
+                .unwrap();
+        }
+        drop(store);
+        // A crash can leave the last line cut short.
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(segments(&dir).last().unwrap())
+            .unwrap();
+        file.write_all(b"{\"t\":").unwrap();
+
+        let newest = read_newest(&dir, 0, 3_000).unwrap();
+        assert_eq!(newest.len(), 3_000);
+        let totals: Vec<u64> = newest.iter().map(|(_, tick)| tick.total.0).collect();
+        let expected: Vec<u64> = (count - 3_000..count).rev().collect();
+        assert_eq!(totals, expected);
+        let (names, tick) = &newest[0];
+        assert_eq!(
+            tick.ifaces(names).collect::<Vec<_>>(),
+            [("en0", (Some(count - 1), Some((count - 1) / 2)))]
+        );
+
+        // Nothing older than `since_us`.
+        let since = now - 10 * 1_000_000;
+        assert_eq!(read_newest(&dir, since, 3_000).unwrap().len(), 10);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
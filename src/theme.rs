@@ -17,6 +17,7 @@ pub struct BorderColors {
     pub outer: Color,
     pub pane: Color,
     pub tile: Color,
+    pub alert: Color,
 }
 
 #[derive(Clone, Debug)]
@@ -26,6 +27,8 @@ pub struct Theme {
     pub pane: Color,
     pub graph: Color,
     pub background: Color,
+    /// Border flash for tiles with a firing alert.
+    pub alert: Color,
 }
 
 fn rgb(hex: &str) -> Color {
@@ -58,6 +61,11 @@ pub fn build_themes() -> Vec<Theme> {
             pane: color,
             graph: color,
             background: Color::Reset,
+            alert: if color == Color::Red {
+                Color::Yellow
+            } else {
+                Color::Red
+            },
         });
     }
 
@@ -68,6 +76,7 @@ pub fn build_themes() -> Vec<Theme> {
             pane: rgb("#ea76cb"),
             graph: rgb("#40a02b"),
             background: rgb("#eff1f5"),
+            alert: rgb("#d20f39"),
         },
         Theme {
             name: "Catppuccin Frappe",
@@ -75,6 +84,7 @@ pub fn build_themes() -> Vec<Theme> {
             pane: rgb("#f4b8e4"),
             graph: rgb("#a6d189"),
             background: rgb("#303446"),
+            alert: rgb("#e78284"),
         },
         Theme {
             name: "Catppuccin Macchiato",
@@ -82,6 +92,7 @@ pub fn build_themes() -> Vec<Theme> {
             pane: rgb("#f5bde6"),
             graph: rgb("#a6da95"),
             background: rgb("#24273a"),
+            alert: rgb("#ed8796"),
         },
         Theme {
             name: "Catppuccin Mocha",
@@ -89,6 +100,7 @@ pub fn build_themes() -> Vec<Theme> {
             pane: rgb("#f5c2e7"),
             graph: rgb("#a6e3a1"),
             background: rgb("#1e1e2e"),
+            alert: rgb("#f38ba8"),
         },
         Theme {
             name: "Dracula",
@@ -96,6 +108,7 @@ pub fn build_themes() -> Vec<Theme> {
             pane: rgb("#ff79c6"),
             graph: rgb("#50fa7b"),
             background: rgb("#282a36"),
+            alert: rgb("#ff5555"),
         },
         Theme {
             name: "Nord",
@@ -103,6 +116,7 @@ pub fn build_themes() -> Vec<Theme> {
             pane: rgb("#81a1c1"),
             graph: rgb("#a3be8c"),
             background: rgb("#2e3440"),
+            alert: rgb("#bf616a"),
         },
         Theme {
             name: "Tokyo Night",
@@ -110,6 +124,7 @@ pub fn build_themes() -> Vec<Theme> {
             pane: rgb("#bb9af7"),
             graph: rgb("#9ece6a"),
             background: rgb("#1a1b26"),
+            alert: rgb("#f7768e"),
         },
         Theme {
             name: "Tokyo Storm",
@@ -117,6 +132,7 @@ pub fn build_themes() -> Vec<Theme> {
             pane: rgb("#bb9af7"),
             graph: rgb("#9ece6a"),
             background: rgb("#24283b"),
+            alert: rgb("#f7768e"),
         },
         Theme {
             name: "Tokyo Moon",
@@ -124,6 +140,7 @@ pub fn build_themes() -> Vec<Theme> {
             pane: rgb("#c099ff"),
             graph: rgb("#c3e88d"),
             background: rgb("#222436"),
+            alert: rgb("#ff757f"),
         },
         Theme {
             name: "Tokyo Day",
@@ -131,6 +148,7 @@ pub fn build_themes() -> Vec<Theme> {
             pane: rgb("#9854f1"),
             graph: rgb("#587539"),
             background: rgb("#e1e2e7"),
+            alert: rgb("#f52a65"),
         },
     ]);
 
@@ -0,0 +1,178 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::net::{InterfaceSampler, Snapshot};
+
+/// Wraps another sampler and appends every snapshot it produces to a JSON
+/// Lines trace, one object per tick.
+pub struct RecordingSampler<S> {
+    inner: S,
+    out: LineWriter<File>,
+}
+
+impl<S: InterfaceSampler> RecordingSampler<S> {
+    pub fn new(inner: S, file: File) -> Self {
+        Self {
+            inner,
+            out: LineWriter::new(file),
+        }
+    }
+}
+
+impl<S: InterfaceSampler> InterfaceSampler for RecordingSampler<S> {
+    fn sample(&mut self) -> io::Result<Snapshot> {
+        let snapshot = self.inner.sample()?;
+        serde_json::to_writer(&mut self.out, &snapshot)?;
+        self.out.write_all(b"\n")?;
+        Ok(snapshot)
+    }
+}
+
+const MIN_SPEED: f64 = 0.125;
+const MAX_SPEED: f64 = 64.0;
+
+/// Parses a playback speed, which must be a finite number above zero.
+/// Values outside the supported range are clamped when the trace loads.
+pub fn parse_speed(text: &str) -> Result<f64, String> {
+    let speed: f64 = text
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid speed \"{text}\""))?;
+    if !speed.is_finite() || speed <= 0.0 {
+        return Err(format!(
+            "speed must be a finite number above 0, not \"{text}\""
+        ));
+    }
+    Ok(speed)
+}
+
+/// Plays back a trace written by `RecordingSampler`, one snapshot per call to
+/// `sample`, with the original spacing between ticks scaled by `speed`.
+pub struct Replay {
+    snapshots: Vec<Snapshot>,
+    cursor: usize,
+    pub speed: f64,
+    pub paused: bool,
+}
+
+impl Replay {
+    pub fn load(path: &Path, speed: f64) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut snapshots = Vec::new();
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let snapshot: Snapshot = serde_json::from_str(&line).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}:{}: {err}", path.display(), idx + 1),
+                )
+            })?;
+            snapshots.push(snapshot);
+        }
+        if snapshots.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: trace is empty", path.display()),
+            ));
+        }
+
+        Ok(Self {
+            snapshots,
+            cursor: 0,
+            speed: speed.clamp(MIN_SPEED, MAX_SPEED),
+            paused: false,
+        })
+    }
+
+    fn offset_of(&self, idx: usize) -> Duration {
+        let first = self.snapshots[0].elapsed_us;
+        Duration::from_micros(self.snapshots[idx].elapsed_us.saturating_sub(first))
+    }
+
+    /// Trace time of the snapshot most recently handed out.
+    pub fn position(&self) -> Duration {
+        self.offset_of(self.cursor.saturating_sub(1))
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.offset_of(self.snapshots.len() - 1)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.cursor >= self.snapshots.len()
+    }
+
+    /// Wall time to wait before the next snapshot is due, or `None` while
+    /// paused or once the trace is exhausted.
+    pub fn next_delay(&self) -> Option<Duration> {
+        if self.paused || self.finished() {
+            return None;
+        }
+        if self.cursor == 0 {
+            return Some(Duration::ZERO);
+        }
+        let gap = self.offset_of(self.cursor) - self.offset_of(self.cursor - 1);
+        Some(gap.div_f64(self.speed))
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed * 2.0).min(MAX_SPEED);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = (self.speed / 2.0).max(MIN_SPEED);
+    }
+
+    /// Index of the snapshot that lands `delta_secs` away from the current
+    /// position, clamped to the trace.
+    pub fn index_at(&self, delta_secs: f64) -> usize {
+        let target = self.position().as_secs_f64() + delta_secs;
+        if target <= 0.0 {
+            return 0;
+        }
+        let target = Duration::from_secs_f64(target);
+        let after = (0..self.snapshots.len())
+            .find(|idx| self.offset_of(*idx) > target)
+            .unwrap_or(self.snapshots.len());
+        after.saturating_sub(1)
+    }
+
+    pub fn rewind_to(&mut self, idx: usize) {
+        self.cursor = idx.min(self.snapshots.len());
+    }
+}
+
+impl InterfaceSampler for Replay {
+    fn sample(&mut self) -> io::Result<Snapshot> {
+        let snapshot = self
+            .snapshots
+            .get(self.cursor)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "end of trace"))?;
+        self.cursor += 1;
+        Ok(snapshot)
+    }
+
+    fn replay(&mut self) -> Option<&mut Replay> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speed_must_be_finite_and_positive() {
+        assert_eq!(parse_speed("2"), Ok(2.0));
+        assert_eq!(parse_speed("0.5"), Ok(0.5));
+        for text in ["0", "-1", "nan", "NaN", "inf", "-inf", "fast", ""] {
+            assert!(parse_speed(text).is_err(), "accepted {text:?}");
+        }
+    }
+}
@@ -1,18 +1,31 @@
 use std::collections::HashSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use ratatui::layout::{Alignment, Constraint, Direction, Layout};
 use ratatui::style::{Color, Modifier, Style};
-use ratatui::symbols::bar;
+use ratatui::symbols::{bar, Marker};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, BorderType, Borders, Paragraph, RenderDirection, Sparkline};
+use ratatui::widgets::{
+    Axis, Block, BorderType, Borders, Chart, Dataset, GraphType, Paragraph, RenderDirection,
+    Sparkline,
+};
 use ratatui::Frame;
 use unicode_width::UnicodeWidthStr;
 
-use crate::app::{App, IfaceState};
-use crate::constants::MIN_TILE_WIDTH;
-use crate::model::{Group, SortMode};
-
-fn format_rate(bytes_per_sec: f64, bits: bool) -> String {
+use crate::alert::Metric;
+use crate::app::{App, IfaceState, Series, WindowSeries};
+use crate::config::matches_any;
+use crate::constants::{
+    ALERT_FLASH_MS, ALERT_PANE_ROWS, EVENT_PANE_HEIGHT, HOUR_ROLLUPS, MIN_TILE_WIDTH,
+    QUOTA_WARN_LEVELS, USAGE_PANE_ROWS,
+};
+use crate::events::{local_time, EventKind};
+use crate::model::{Group, SortMode, Window};
+use crate::net::{flag_names, Family};
+use crate::rollup::Stats;
+use crate::theme::BorderColors;
+
+pub fn format_rate(bytes_per_sec: f64, bits: bool) -> String {
     let step = if bits { 1000.0 } else { 1024.0 };
     let units = if bits {
         ["b/s", "Kb/s", "Mb/s", "Gb/s", "Tb/s"]
@@ -41,24 +54,177 @@ fn format_rate(bytes_per_sec: f64, bits: bool) -> String {
     }
 }
 
-fn sparkline_data(history: &[u64], width: u16) -> Vec<u64> {
+/// Whether alerting tiles show the alert color right now, and how long until
+/// that flips. Runs off the wall clock so the flash keeps a steady beat
+/// whatever the sample interval.
+pub fn alert_flash() -> (bool, Duration) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let phase = now % (2 * ALERT_FLASH_MS);
+    let until_flip = ALERT_FLASH_MS - phase % ALERT_FLASH_MS;
+    (phase < ALERT_FLASH_MS, Duration::from_millis(until_flip))
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB", "PB"];
+    let mut value = bytes as f64;
+    let mut idx = 0usize;
+    while value >= 1024.0 && idx < units.len() - 1 {
+        value /= 1024.0;
+        idx += 1;
+    }
+    if idx == 0 {
+        format!("{} {}", bytes, units[idx])
+    } else {
+        format!("{:.2} {}", value, units[idx])
+    }
+}
+
+fn format_count(value: f64) -> String {
+    let units = ["", "k", "M", "G"];
+    let mut value = value;
+    let mut idx = 0usize;
+    while value >= 1000.0 && idx < units.len() - 1 {
+        value /= 1000.0;
+        idx += 1;
+    }
+    if idx == 0 || value >= 100.0 {
+        format!("{:.0}{}", value, units[idx])
+    } else {
+        format!("{:.1}{}", value, units[idx])
+    }
+}
+
+/// Link speeds are quoted in decimal bits regardless of the bits/bytes toggle.
+fn format_link_speed(bits_per_sec: u64) -> String {
+    let units = ["b/s", "Kb/s", "Mb/s", "Gb/s", "Tb/s"];
+    let mut value = bits_per_sec as f64;
+    let mut idx = 0usize;
+    while value >= 1000.0 && idx < units.len() - 1 {
+        value /= 1000.0;
+        idx += 1;
+    }
+    if value.fract() == 0.0 {
+        format!("{:.0} {}", value, units[idx])
+    } else {
+        format!("{:.1} {}", value, units[idx])
+    }
+}
+
+/// Red for anything that is counting errors or drops, plain otherwise.
+fn error_style(faulty: bool) -> Style {
+    if faulty {
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    }
+}
+
+fn format_span(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, secs / 60 % 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// The newest `width` values of a newest-first `history`, padded out with the
+/// oldest one when there are fewer.
+fn sparkline_data<T: Copy + Default>(history: impl IntoIterator<Item = T>, width: u16) -> Vec<T> {
     let width = width as usize;
     if width == 0 {
         return Vec::new();
     }
 
-    if history.is_empty() {
-        return vec![0; width];
+    let mut data = history.into_iter().take(width).collect::<Vec<T>>();
+    if data.is_empty() {
+        return vec![T::default(); width];
     }
-
-    let mut data = history.iter().take(width).copied().collect::<Vec<u64>>();
     if data.len() < width {
-        let pad = *data.last().unwrap_or(&0);
+        let pad = *data.last().unwrap_or(&T::default());
         data.resize(width, pad);
     }
     data
 }
 
+/// While paused, how many of the newest points to skip so the scrollback
+/// cursor stays on screen in `width` columns, along with the cursor's column
+/// counted from the right edge.
+fn scrolled(app: &App, width: u16) -> (usize, Option<usize>) {
+    let Some(scrollback) = &app.scrollback else {
+        return (0, None);
+    };
+    let start = scrollback
+        .cursor
+        .saturating_sub((width as usize).saturating_sub(1));
+    (start, Some(scrollback.cursor - start))
+}
+
+/// Time axis label for a sparkline `width` columns wide: how far back its
+/// left edge reaches. Left off while paused, where the cursor shows the time.
+fn axis_line(app: &App, series: &WindowSeries, width: u16) -> Option<Line<'static>> {
+    if app.scrollback.is_some() || width == 0 {
+        return None;
+    }
+    let span = (width as f64 * series.step_secs).min(app.display.window.secs() as f64);
+    Some(title_line(&format!("◂ {}", format_span(span))).alignment(Alignment::Center))
+}
+
+/// Marks the scrollback cursor's column in a sparkline drawn in `inner`.
+fn draw_cursor(f: &mut Frame<'_>, inner: ratatui::layout::Rect, column: usize) {
+    if column >= inner.width as usize {
+        return;
+    }
+    let x = inner.right() - 1 - column as u16;
+    let buf = f.buffer_mut();
+    for y in inner.top()..inner.bottom() {
+        if let Some(cell) = buf.cell_mut((x, y)) {
+            cell.modifier.toggle(Modifier::REVERSED);
+        }
+    }
+}
+
+/// Time and rates under the scrollback cursor, for a sparkline's bottom
+/// border.
+fn cursor_line(
+    app: &App,
+    series: &WindowSeries,
+    rx_style: Style,
+    tx_style: Style,
+) -> Option<Line<'static>> {
+    let cursor = app.scrollback.as_ref()?.cursor;
+    let back_us = (cursor as f64 * series.step_secs * 1_000_000.0) as u64;
+    let time = UNIX_EPOCH + Duration::from_micros(series.time_us.saturating_sub(back_us));
+    let clock = match local_time(time) {
+        Some(tm) => format!("{:02}:{:02}:{:02}", tm.tm_hour, tm.tm_min, tm.tm_sec),
+        None => "--:--:--".to_string(),
+    };
+    let rate = |history: &Series<'_>| match history.clone().nth(cursor) {
+        Some(Some(value)) => format_rate(value as f64, app.display.show_bits),
+        Some(None) => "gap".to_string(),
+        None => "-".to_string(),
+    };
+    Some(Line::from(vec![
+        Span::raw(format!(" {clock}  ")),
+        Span::styled(format!("RX {}", rate(&series.rx)), rx_style),
+        Span::raw("  "),
+        Span::styled(format!("TX {}", rate(&series.tx)), tx_style),
+        Span::raw(" "),
+    ]))
+}
+
+/// Drawn down the full height of a sparkline column where the counters reset.
+const GAP_SYMBOL: &str = "┊";
+
+fn gap_style() -> Style {
+    Style::default().fg(Color::Yellow)
+}
+
 fn bar_symbol(level: u64) -> &'static str {
     match level {
         0 => bar::NINE_LEVELS.empty,
@@ -99,11 +265,12 @@ fn render_split_sparkline(
     f: &mut Frame<'_>,
     area: ratatui::layout::Rect,
     block: Block<'_>,
-    rx: &[u64],
-    tx: &[u64],
+    rx: impl IntoIterator<Item = Option<u64>>,
+    tx: impl IntoIterator<Item = Option<u64>>,
     rx_style: Style,
     tx_style: Style,
     base_style: Style,
+    ceiling: Option<u64>,
 ) {
     let inner = block.inner(area);
     f.render_widget(block, area);
@@ -118,8 +285,13 @@ fn render_split_sparkline(
     let rx_data = sparkline_data(rx, inner.width);
     let tx_data = sparkline_data(tx, inner.width);
 
-    let mut max_rx = rx_data.iter().copied().max().unwrap_or(0);
-    let mut max_tx = tx_data.iter().copied().max().unwrap_or(0);
+    let (mut max_rx, mut max_tx) = match ceiling {
+        Some(ceiling) => (ceiling, ceiling),
+        None => (
+            rx_data.iter().flatten().copied().max().unwrap_or(0),
+            tx_data.iter().flatten().copied().max().unwrap_or(0),
+        ),
+    };
     if max_rx == 0 {
         max_rx = 1;
     }
@@ -141,8 +313,17 @@ fn render_split_sparkline(
 
     for i in 0..inner.width as usize {
         let x = inner.right().saturating_sub(1 + i as u16);
-        let rx_val = rx_data.get(i).copied().unwrap_or(0);
-        let tx_val = tx_data.get(i).copied().unwrap_or(0);
+        let (Some(rx_val), Some(tx_val)) = (
+            rx_data.get(i).copied().unwrap_or(Some(0)),
+            tx_data.get(i).copied().unwrap_or(Some(0)),
+        ) else {
+            for y in inner.top()..inner.bottom() {
+                if let Some(cell) = buf.cell_mut((x, y)) {
+                    cell.set_symbol(GAP_SYMBOL).set_style(gap_style());
+                }
+            }
+            continue;
+        };
 
         let rx_units = scale_units(rx_val, max_rx, up_units);
         let tx_units = scale_units(tx_val, max_tx, down_units);
@@ -280,44 +461,69 @@ fn render_overview(f: &mut Frame<'_>, area: ratatui::layout::Rect, app: &App) {
         app.block_style(),
     );
 
+    let inner = block.inner(area);
+    let series = app.window_series(None);
+    let (skip, cursor) = scrolled(app, inner.width);
+    let rx = series.rx.clone().skip(skip);
+    let tx = series.tx.clone().skip(skip);
+    let block = match cursor_line(app, &series, app.rx_style(), app.tx_style()) {
+        Some(line) => block.title_bottom(line),
+        None => block,
+    };
+    let block = match axis_line(app, &series, inner.width) {
+        Some(line) => block.title_bottom(line),
+        None => block,
+    };
     if app.display.show_split {
         render_split_sparkline(
             f,
             area,
             block,
-            &app.total_rx_history,
-            &app.total_tx_history,
+            rx,
+            tx,
             app.rx_style(),
             app.tx_style(),
             app.block_style(),
+            None,
         );
     } else {
-        let inner = block.inner(area);
-        let data = sparkline_data(&app.total_rx_history, inner.width)
+        let data = sparkline_data(rx, inner.width)
             .into_iter()
-            .zip(sparkline_data(&app.total_tx_history, inner.width))
-            .map(|(rx, tx)| rx + tx)
-            .collect::<Vec<u64>>();
+            .zip(sparkline_data(tx, inner.width))
+            .map(|(rx, tx)| Some(rx? + tx?))
+            .collect::<Vec<Option<u64>>>();
         let spark = Sparkline::default()
             .block(block)
             .direction(RenderDirection::RightToLeft)
             .data(&data)
-            .style(app.rx_style());
+            .style(app.rx_style())
+            .absent_value_symbol(GAP_SYMBOL)
+            .absent_value_style(gap_style());
         f.render_widget(spark, area);
     }
+    if let Some(column) = cursor {
+        draw_cursor(f, inner, column);
+    }
 }
 
-fn render_interface(f: &mut Frame<'_>, area: ratatui::layout::Rect, app: &App, iface: &IfaceState) {
-    let bsd_name = iface.name.as_str();
-    let friendly_name = app.aliases.get(bsd_name).map(|alias| {
+fn friendly_name(app: &App, bsd_name: &str) -> Option<String> {
+    app.aliases.get(bsd_name).map(|alias| {
         if alias == bsd_name || alias.contains(&format!("({})", bsd_name)) {
             alias.to_string()
         } else {
             format!("{alias} ({bsd_name})")
         }
-    });
+    })
+}
 
-    let label_r = format_rate(iface.total_rate, app.display.show_bits);
+fn render_interface(f: &mut Frame<'_>, area: ratatui::layout::Rect, app: &App, iface: &IfaceState) {
+    let bsd_name = iface.name.as_str();
+    let friendly_name = friendly_name(app, bsd_name);
+
+    let mut label_r = format_rate(iface.total_rate, app.display.show_bits);
+    if let Some(pct) = iface.utilization() {
+        label_r.push_str(&format!("  {pct:.0}%"));
+    }
     let right_width = UnicodeWidthStr::width(label_r.as_str()) + 2;
     let available = area.width.saturating_sub(2) as usize;
     let max_left = available.saturating_sub(right_width + 1);
@@ -343,54 +549,137 @@ fn render_interface(f: &mut Frame<'_>, area: ratatui::layout::Rect, app: &App, i
     };
 
     let colors = app.colors();
+    // Down links keep their tile and frozen history, drawn in grey.
+    let (rx_style, tx_style) = if iface.up {
+        (app.rx_style(), app.tx_style())
+    } else {
+        (
+            app.rx_style().fg(Color::DarkGray),
+            app.tx_style().fg(Color::DarkGray),
+        )
+    };
     let label_l_line = {
         let rx = format_rate(iface.rx_rate, app.display.show_bits);
         let tx = format_rate(iface.tx_rate, app.display.show_bits);
         let mut spans = Vec::new();
+        let marker = if app.is_pinned(bsd_name) { "◆ " } else { "" };
+        let name_style = if app.is_hidden(bsd_name) || !iface.up {
+            bold_title_style(Color::DarkGray)
+        } else {
+            bold_title_style(colors.tile)
+        };
         spans.push(Span::styled(
-            format!(" {}", name_display),
-            bold_title_style(colors.tile),
+            format!(" {marker}{}", name_display),
+            name_style,
         ));
         spans.push(Span::raw("  "));
-        spans.push(Span::styled(format!("RX {rx}"), app.rx_style()));
-        spans.push(Span::raw("  "));
-        spans.push(Span::styled(format!("TX {tx}"), app.tx_style()));
+        if iface.up {
+            spans.push(Span::styled(format!("RX {rx}"), rx_style));
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(format!("TX {tx}"), tx_style));
+        } else {
+            spans.push(Span::styled("down", rx_style));
+        }
         spans.push(Span::raw(" "));
         Line::from(spans)
     };
     let label_r_line = Some(title_line(&label_r).alignment(Alignment::Right));
+    let focused = app.focus.as_deref() == Some(bsd_name);
+    let (border_color, border_type) = if focused {
+        (colors.outer, BorderType::Double)
+    } else if !iface.up {
+        (Color::DarkGray, BorderType::Plain)
+    } else {
+        (colors.tile, BorderType::Plain)
+    };
+    // Alerting tiles flash between the alert color and their usual one.
+    let border_color = if app.alerts.is_alerting(bsd_name) && alert_flash().0 {
+        colors.alert
+    } else {
+        border_color
+    };
+    let mut footer = vec![Span::raw(format!(
+        " {}/{} pkt/s ",
+        format_count(iface.rx_packet_rate),
+        format_count(iface.tx_packet_rate)
+    ))];
+    if iface.error_rate > 0.0 || iface.drop_rate > 0.0 {
+        footer.push(Span::styled(
+            format!(
+                "err {}/s  drop {}/s",
+                format_count(iface.error_rate),
+                format_count(iface.drop_rate)
+            ),
+            error_style(true),
+        ));
+        footer.push(Span::raw(" "));
+    }
     let block = bordered_block(
-        colors.tile,
-        BorderType::Plain,
+        border_color,
+        border_type,
         Some(label_l_line),
         label_r_line,
         app.block_style(),
-    );
+    )
+    .title_bottom(Line::from(footer));
+    let block = match iface.primary_address() {
+        Some(address) => {
+            block.title_bottom(title_line(&address.to_string()).alignment(Alignment::Right))
+        }
+        None => block,
+    };
+    let ceiling = iface
+        .capacity()
+        .filter(|_| app.display.link_scale)
+        .map(|cap| cap.round() as u64);
+    let inner = block.inner(area);
+    let series = app.window_series(Some(bsd_name));
+    let (skip, cursor) = scrolled(app, inner.width);
+    let rx = series.rx.clone().skip(skip);
+    let tx = series.tx.clone().skip(skip);
+    let block = match cursor_line(app, &series, rx_style, tx_style) {
+        Some(line) => block.title_bottom(line),
+        None => block,
+    };
+    let block = match axis_line(app, &series, inner.width) {
+        Some(line) => block.title_bottom(line),
+        None => block,
+    };
     if app.display.show_split {
         render_split_sparkline(
             f,
             area,
             block,
-            &iface.rx_history,
-            &iface.tx_history,
-            app.rx_style(),
-            app.tx_style(),
+            rx,
+            tx,
+            rx_style,
+            tx_style,
             app.block_style(),
+            ceiling,
         );
     } else {
-        let inner = block.inner(area);
-        let data = sparkline_data(&iface.rx_history, inner.width)
+        let data = sparkline_data(rx, inner.width)
             .into_iter()
-            .zip(sparkline_data(&iface.tx_history, inner.width))
-            .map(|(rx, tx)| rx + tx)
-            .collect::<Vec<u64>>();
-        let spark = Sparkline::default()
+            .zip(sparkline_data(tx, inner.width))
+            .map(|(rx, tx)| Some(rx? + tx?))
+            .collect::<Vec<Option<u64>>>();
+        let mut spark = Sparkline::default()
             .block(block)
             .direction(RenderDirection::RightToLeft)
             .data(&data)
-            .style(app.rx_style());
+            .style(rx_style)
+            .absent_value_symbol(GAP_SYMBOL)
+            .absent_value_style(gap_style());
+        if let Some(ceiling) = ceiling {
+            // Combined RX+TX can exceed one direction's capacity; the widget
+            // clamps those bars at full height.
+            spark = spark.max(ceiling);
+        }
         f.render_widget(spark, area);
     }
+    if let Some(column) = cursor {
+        draw_cursor(f, inner, column);
+    }
 }
 
 fn render_group(
@@ -404,6 +693,7 @@ fn render_group(
         .states
         .values()
         .filter(|s| app.in_group(s, group))
+        .filter(|s| app.display.show_hidden || !app.is_hidden(&s.name))
         .collect();
 
     let filter_loopback = group == Group::Virtual && !app.display.show_loopback;
@@ -414,7 +704,7 @@ fn render_group(
         candidates_all.len()
     };
 
-    let visible_names: Vec<String> = if app.display.show_inactive {
+    let mut visible_names: Vec<String> = if app.display.show_inactive {
         candidates_all
             .iter()
             .filter(|s| !filter_loopback || !s.is_loopback)
@@ -447,9 +737,12 @@ fn render_group(
             let tile_width = inner_width / cols as u16;
             let window_samples = tile_width.saturating_sub(2).max(1) as u64;
 
+            // A tile that goes down stays put, greyed out, until it returns.
             visible_set.retain(|name| {
                 if let Some(iface) = app.states.get(name) {
-                    app.sample_index.saturating_sub(iface.last_active_sample) < window_samples
+                    !iface.up
+                        || app.sample_index.saturating_sub(iface.last_active_sample)
+                            < window_samples
                 } else {
                     false
                 }
@@ -469,20 +762,28 @@ fn render_group(
             .collect()
     };
 
+    for iface in &candidates_all {
+        if app.is_pinned(&iface.name) && !visible_names.contains(&iface.name) {
+            visible_names.push(iface.name.clone());
+        }
+    }
+
     let mut visible: Vec<&IfaceState> = visible_names
         .iter()
         .filter_map(|name| app.states.get(name))
         .collect();
 
-    match app.sort_mode {
-        SortMode::Bandwidth => visible.sort_by(|a, b| {
-            b.total_rate
+    visible.sort_by(|a, b| {
+        let pinned = app.is_pinned(&b.name).cmp(&app.is_pinned(&a.name));
+        pinned.then_with(|| match app.sort_mode {
+            SortMode::Bandwidth => b
+                .total_rate
                 .partial_cmp(&a.total_rate)
                 .unwrap_or(std::cmp::Ordering::Equal)
-                .then_with(|| a.name.cmp(&b.name))
-        }),
-        SortMode::Name => visible.sort_by(|a, b| a.name.cmp(&b.name)),
-    }
+                .then_with(|| a.name.cmp(&b.name)),
+            SortMode::Name => a.name.cmp(&b.name),
+        })
+    });
 
     let mut group_rx = 0.0;
     let mut group_tx = 0.0;
@@ -561,6 +862,7 @@ fn render_group(
         remaining_w = remaining_w.saturating_sub(width);
     }
 
+    let mut drawn = Vec::with_capacity(visible.len());
     let mut idx = 0usize;
     for row_area in row_rects.iter().take(rows) {
         let col_rects = Layout::default()
@@ -573,9 +875,686 @@ fn render_group(
                 break;
             }
             render_interface(f, *col_area, app, visible[idx]);
+            drawn.push((visible[idx].name.clone(), *col_area));
             idx += 1;
         }
     }
+
+    app.tiles.extend(drawn);
+}
+
+/// min, avg, max and 95th percentile of a rate history.
+/// Gaps left by counter resets are skipped.
+fn history_stats(history: impl IntoIterator<Item = Option<u64>>) -> [u64; 4] {
+    let mut sorted = history.into_iter().flatten().collect::<Vec<u64>>();
+    if sorted.is_empty() {
+        return [0; 4];
+    }
+    sorted.sort_unstable();
+    let sum: u128 = sorted.iter().map(|v| *v as u128).sum();
+    let avg = (sum / sorted.len() as u128) as u64;
+    let p95_idx = ((sorted.len() as f64 * 0.95).ceil() as usize).clamp(1, sorted.len()) - 1;
+    [sorted[0], avg, sorted[sorted.len() - 1], sorted[p95_idx]]
+}
+
+/// Like `history_stats`, but with the extremes and average taken from the
+/// rollup buckets themselves rather than from their per-column averages.
+fn rollup_stats(stats: Stats, series: impl IntoIterator<Item = Option<u64>>) -> [u64; 4] {
+    let [_, _, _, p95] = history_stats(series);
+    [stats.min, stats.avg().unwrap_or(0), stats.max, p95]
+}
+
+fn stat_line(
+    label: &str,
+    current: f64,
+    [min, avg, max, p95]: [u64; 4],
+    style: Style,
+    format: impl Fn(f64) -> String,
+) -> Line<'static> {
+    let mut spans = vec![Span::styled(format!(" {label:<8}"), style)];
+    for value in [current, min as f64, avg as f64, max as f64, p95 as f64] {
+        spans.push(Span::raw(format!("{:>13}", format(value))));
+    }
+    Line::from(spans)
+}
+
+fn address_line(
+    heading: &'static str,
+    label: Style,
+    iface: &IfaceState,
+    family: Family,
+) -> Line<'static> {
+    let addresses = iface
+        .addresses
+        .iter()
+        .filter(|a| a.family == family)
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    let text = if addresses.is_empty() {
+        "-".to_string()
+    } else {
+        addresses.join("  ")
+    };
+    Line::from(vec![Span::styled(heading, label), Span::raw(text)])
+}
+
+fn render_detail(f: &mut Frame<'_>, area: ratatui::layout::Rect, app: &App, iface: &IfaceState) {
+    let colors = app.colors();
+    let bits = app.display.show_bits;
+    let name = friendly_name(app, &iface.name).unwrap_or_else(|| iface.name.clone());
+    let block = bordered_block(
+        colors.pane,
+        BorderType::Thick,
+        Some(title_line_bold(&name, "", colors.pane)),
+        Some(
+            title_line(&format!("Total {}", format_rate(iface.total_rate, bits)))
+                .alignment(Alignment::Right),
+        ),
+        app.block_style(),
+    );
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let label = Style::default().add_modifier(Modifier::BOLD);
+    let group = match iface.group() {
+        Group::Physical => "physical",
+        Group::Virtual => "virtual",
+    };
+    let alias = app
+        .aliases
+        .get(&iface.name)
+        .map(String::as_str)
+        .unwrap_or("-");
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(" Interface ", label),
+            Span::raw(format!("{:<16}", iface.name)),
+            Span::styled(" Alias ", label),
+            Span::raw(format!("{alias:<24}")),
+            Span::styled(" Group ", label),
+            Span::raw(group),
+        ]),
+        Line::from(vec![
+            Span::styled(" State     ", label),
+            if iface.up {
+                Span::raw("up")
+            } else {
+                Span::styled("down", Style::default().fg(Color::DarkGray))
+            },
+            Span::raw(format!("  transitions {}", iface.transitions)),
+        ]),
+        Line::from(vec![
+            Span::styled(" Flags     ", label),
+            Span::raw(format!(
+                "{} <{:#x}>",
+                flag_names(iface.flags).join(" "),
+                iface.flags
+            )),
+        ]),
+        Line::from(vec![
+            Span::styled(" Link      ", label),
+            Span::raw(match iface.link_speed {
+                0 => "speed unknown".to_string(),
+                speed => format_link_speed(speed),
+            }),
+            Span::raw(format!("  MTU {}", iface.mtu)),
+            Span::raw(match iface.utilization() {
+                Some(pct) => format!("  utilization {pct:.1}%"),
+                None => String::new(),
+            }),
+        ]),
+        Line::from(vec![
+            Span::styled(" MAC       ", label),
+            Span::raw(iface.mac.as_deref().unwrap_or("-").to_string()),
+        ]),
+        address_line(" IPv4      ", label, iface, Family::Inet),
+        address_line(" IPv6      ", label, iface, Family::Inet6),
+        Line::from(vec![
+            Span::styled(" Session   ", label),
+            Span::styled(
+                format!("RX {}", format_bytes(iface.session_rx_bytes)),
+                app.rx_style(),
+            ),
+            Span::raw("  "),
+            Span::styled(
+                format!("TX {}", format_bytes(iface.session_tx_bytes)),
+                app.tx_style(),
+            ),
+            Span::raw(format!(
+                "  (counters RX {}  TX {})",
+                format_bytes(iface.rx_bytes),
+                format_bytes(iface.tx_bytes)
+            )),
+        ]),
+        usage_line(label, app, iface),
+        Line::from(vec![
+            Span::styled(" Packets   ", label),
+            Span::raw(format!(
+                "RX {}  TX {}  multicast RX {}  TX {}",
+                iface.rx_packets, iface.tx_packets, iface.rx_multicast, iface.tx_multicast
+            )),
+        ]),
+        Line::from(vec![
+            Span::styled(" Faults    ", label),
+            Span::styled(
+                format!(
+                    "errors RX {}  TX {}  drops RX {}  TX {}  collisions {}",
+                    iface.rx_errors,
+                    iface.tx_errors,
+                    iface.rx_drops,
+                    iface.tx_drops,
+                    iface.collisions
+                ),
+                error_style(
+                    iface.rx_errors + iface.tx_errors + iface.rx_drops + iface.tx_drops > 0,
+                ),
+            ),
+            Span::styled(
+                format!("  counter resets {}", iface.counter_resets),
+                if iface.counter_resets > 0 {
+                    gap_style()
+                } else {
+                    Style::default()
+                },
+            ),
+        ]),
+        Line::from(""),
+    ];
+    let window = app.display.window;
+    let series = app.window_series(Some(&iface.name));
+    let mut header = vec![Span::styled(format!(" {:<8}", window.label()), label)];
+    for col in ["current", "min", "avg", "max", "p95"] {
+        header.push(Span::styled(format!("{col:>13}"), label));
+    }
+    lines.push(Line::from(header));
+    let rate = |v: f64| format_rate(v, bits);
+    let per_sec = |v: f64| format!("{}/s", format_count(v));
+    let now = app.sample_time_us / 1_000_000;
+    let rollups = app.rollups.get(&iface.name);
+    let (rx_stats, tx_stats) = match rollups {
+        Some(rollups) if window != Window::FiveMinutes => {
+            let (rx, tx) = rollups.minute.summary(now, window.secs());
+            (
+                rollup_stats(rx, series.rx.clone()),
+                rollup_stats(tx, series.tx.clone()),
+            )
+        }
+        _ => (
+            history_stats(series.rx.clone()),
+            history_stats(series.tx.clone()),
+        ),
+    };
+    lines.push(stat_line(
+        "RX",
+        iface.rx_rate,
+        rx_stats,
+        app.rx_style(),
+        rate,
+    ));
+    lines.push(stat_line(
+        "TX",
+        iface.tx_rate,
+        tx_stats,
+        app.tx_style(),
+        rate,
+    ));
+    // Packet and error rates only keep raw history.
+    lines.push(stat_line(
+        "RX pkt",
+        iface.rx_packet_rate,
+        history_stats(iface.rx_packet_history.iter().map(|p| p.value)),
+        app.rx_style(),
+        per_sec,
+    ));
+    lines.push(stat_line(
+        "TX pkt",
+        iface.tx_packet_rate,
+        history_stats(iface.tx_packet_history.iter().map(|p| p.value)),
+        app.tx_style(),
+        per_sec,
+    ));
+    lines.push(stat_line(
+        "Errors",
+        iface.error_rate,
+        history_stats(iface.error_history.iter().map(|p| p.value)),
+        error_style(iface.error_rate > 0.0),
+        per_sec,
+    ));
+    if let Some(rollups) = rollups {
+        let month = HOUR_ROLLUPS as u64 * rollups.hour.width;
+        let (rx, tx) = rollups.hour.summary(now, month);
+        let (rx_series, tx_series) = rollups.hour.series(now, month, rollups.hour.width);
+        lines.push(stat_line(
+            "RX 30d",
+            iface.rx_rate,
+            rollup_stats(rx, rx_series),
+            app.rx_style(),
+            rate,
+        ));
+        lines.push(stat_line(
+            "TX 30d",
+            iface.tx_rate,
+            rollup_stats(tx, tx_series),
+            app.tx_style(),
+            rate,
+        ));
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(lines.len() as u16 + 1),
+            Constraint::Min(0),
+        ])
+        .split(inner);
+    f.render_widget(Paragraph::new(lines).style(app.block_style()), chunks[0]);
+
+    let points = |history: &Series<'_>| -> Vec<(f64, f64)> {
+        history
+            .clone()
+            .enumerate()
+            .filter_map(|(i, v)| Some((-(i as f64) * series.step_secs, v? as f64)))
+            .collect()
+    };
+    let rx_points = points(&series.rx);
+    let tx_points = points(&series.tx);
+    let span = series.rx.clone().count().saturating_sub(1).max(1) as f64 * series.step_secs;
+    let max = rx_points
+        .iter()
+        .chain(&tx_points)
+        .map(|(_, v)| *v)
+        .fold(1.0, f64::max);
+    let max = iface
+        .capacity()
+        .filter(|_| app.display.link_scale)
+        .unwrap_or(max);
+
+    let axis_style = Style::default().fg(colors.pane);
+    let chart = Chart::new(vec![
+        Dataset::default()
+            .name("RX")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(app.rx_style())
+            .data(&rx_points),
+        Dataset::default()
+            .name("TX")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(app.tx_style())
+            .data(&tx_points),
+    ])
+    .style(app.block_style())
+    .x_axis(
+        Axis::default()
+            .style(axis_style)
+            .bounds([-span, 0.0])
+            .labels([
+                format!("-{}", format_span(span)),
+                format!("-{}", format_span(span / 2.0)),
+                "now".to_string(),
+            ]),
+    )
+    .y_axis(
+        Axis::default()
+            .style(axis_style)
+            .bounds([0.0, max])
+            .labels([
+                format_rate(0.0, bits),
+                format_rate(max / 2.0, bits),
+                format_rate(max, bits),
+            ]),
+    );
+    f.render_widget(chart, chunks[1]);
+}
+
+fn usage_line(label: Style, app: &App, iface: &IfaceState) -> Line<'static> {
+    let Some(usage) = &app.usage else {
+        return Line::from(vec![
+            Span::styled(" Usage     ", label),
+            Span::raw("not tracked during replay"),
+        ]);
+    };
+    let today = usage.today(&iface.name);
+    let month = usage.month(&iface.name);
+    let mut spans = vec![
+        Span::styled(" Usage     ", label),
+        Span::raw(format!(
+            "today RX {}  TX {}  month RX {}  TX {}  (since {})",
+            format_bytes(today.rx),
+            format_bytes(today.tx),
+            format_bytes(month.rx),
+            format_bytes(month.tx),
+            usage.period()
+        )),
+    ];
+    if let Some(percent) = usage.quota_percent(&iface.name) {
+        spans.push(Span::styled(
+            format!("  {percent:.0}% of quota"),
+            quota_style(percent, &app.colors()),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// Plain under the first warning level, alert-coloured past it and bold once
+/// the quota is used up.
+fn quota_style(percent: f64, colors: &BorderColors) -> Style {
+    if percent >= 100.0 {
+        Style::default()
+            .fg(colors.alert)
+            .add_modifier(Modifier::BOLD)
+    } else if percent >= QUOTA_WARN_LEVELS[0] as f64 {
+        Style::default().fg(colors.alert)
+    } else {
+        Style::default()
+    }
+}
+
+/// Interfaces with traffic to report in the usage pane, busiest this month
+/// first.
+fn usage_rows(app: &App) -> Vec<&IfaceState> {
+    let Some(usage) = &app.usage else {
+        return Vec::new();
+    };
+    let mut rows: Vec<&IfaceState> = app
+        .states
+        .values()
+        .filter(|s| app.display.show_loopback || !s.is_loopback)
+        .filter(|s| !app.is_hidden(&s.name))
+        .filter(|s| usage.month(&s.name).total() > 0 || s.session_rx_bytes + s.session_tx_bytes > 0)
+        .collect();
+    rows.sort_by(|a, b| {
+        usage
+            .month(&b.name)
+            .total()
+            .cmp(&usage.month(&a.name).total())
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    rows
+}
+
+fn usage_pane_height(app: &App) -> u16 {
+    // Borders and header, plus at least one row for the placeholder.
+    usage_rows(app).len().clamp(1, USAGE_PANE_ROWS) as u16 + 3
+}
+
+fn render_usage(f: &mut Frame<'_>, area: ratatui::layout::Rect, app: &App) {
+    let colors = app.colors();
+    let right = match app.usage.as_ref() {
+        Some(usage) => match &usage.quota {
+            Some(quota) => format!("month from {}  quota {}", usage.period(), quota.text),
+            None => format!("month from {}", usage.period()),
+        },
+        None => "replay".to_string(),
+    };
+    let block = bordered_block(
+        colors.pane,
+        BorderType::Thick,
+        Some(title_line_bold("Data Usage", "", colors.pane)),
+        Some(title_line(&right).alignment(Alignment::Right)),
+        app.block_style(),
+    );
+    let Some(usage) = &app.usage else {
+        f.render_widget(
+            Paragraph::new(" not tracked during replay")
+                .block(block)
+                .style(app.block_style()),
+            area,
+        );
+        return;
+    };
+
+    let rows = usage_rows(app);
+    let name_width = rows
+        .iter()
+        .map(|s| UnicodeWidthStr::width(s.name.as_str()))
+        .max()
+        .unwrap_or(0)
+        .max("Interface".len());
+    let cell = |text: String| format!("{text:>11}");
+    let label = Style::default().add_modifier(Modifier::BOLD);
+    let mut header = vec![Span::styled(
+        format!(
+            " {:<name_width$} {}{}{}{}{}",
+            "Interface",
+            cell("Session".into()),
+            cell("Today".into()),
+            cell("Month RX".into()),
+            cell("Month TX".into()),
+            cell("Month".into()),
+        ),
+        label,
+    )];
+    if usage.quota.is_some() {
+        header.push(Span::styled(cell("Quota".into()), label));
+    }
+    let mut lines = vec![Line::from(header)];
+    for iface in rows
+        .iter()
+        .take(block.inner(area).height.saturating_sub(1) as usize)
+    {
+        let today = usage.today(&iface.name);
+        let month = usage.month(&iface.name);
+        let mut spans = vec![
+            Span::styled(
+                format!(" {:<name_width$} ", iface.name),
+                bold_title_style(colors.tile),
+            ),
+            Span::raw(cell(format_bytes(
+                iface.session_rx_bytes + iface.session_tx_bytes,
+            ))),
+            Span::raw(cell(format_bytes(today.total()))),
+            Span::styled(cell(format_bytes(month.rx)), app.rx_style()),
+            Span::styled(cell(format_bytes(month.tx)), app.tx_style()),
+            Span::styled(cell(format_bytes(month.total())), label),
+        ];
+        if let Some(percent) = usage.quota_percent(&iface.name) {
+            spans.push(Span::styled(
+                cell(format!("{percent:.0}%")),
+                quota_style(percent, &colors),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+    if rows.is_empty() {
+        lines.push(Line::from(" no traffic counted yet"));
+    }
+    f.render_widget(
+        Paragraph::new(lines).block(block).style(app.block_style()),
+        area,
+    );
+}
+
+fn event_style(kind: EventKind, colors: &BorderColors) -> Style {
+    match kind {
+        EventKind::Error => error_style(true),
+        EventKind::Alert => Style::default()
+            .fg(colors.alert)
+            .add_modifier(Modifier::BOLD),
+        EventKind::AlertCleared => Style::default().fg(colors.alert),
+        EventKind::CounterReset => gap_style(),
+        EventKind::Down | EventKind::Disappeared => Style::default().fg(Color::DarkGray),
+        EventKind::Up | EventKind::Appeared => Style::default().fg(Color::Green),
+        EventKind::AddressChanged | EventKind::AliasChanged | EventKind::Hook => Style::default(),
+    }
+}
+
+fn format_metric(metric: Metric, value: f64, bits: bool) -> String {
+    if metric.is_byte_rate() {
+        format_rate(value, bits).trim_start().to_string()
+    } else if metric == Metric::Util {
+        format!("{value:.1}%")
+    } else {
+        format!("{}/s", format_count(value))
+    }
+}
+
+fn render_alerts(f: &mut Frame<'_>, area: ratatui::layout::Rect, app: &App) {
+    let colors = app.colors();
+    let active = app.alerts.active();
+    let block = bordered_block(
+        colors.alert,
+        BorderType::Thick,
+        Some(title_line_bold(
+            "Active Alerts",
+            &format!(" ({})", active.len()),
+            colors.alert,
+        )),
+        None,
+        app.block_style(),
+    );
+    let name_width = active
+        .iter()
+        .map(|b| UnicodeWidthStr::width(b.iface.as_str()))
+        .max()
+        .unwrap_or(0);
+    let lines = active
+        .iter()
+        .map(|breach| {
+            let rule = &app.alerts.rules[breach.rule];
+            let held = app.last_sample_us.saturating_sub(breach.since_us) as f64 / 1_000_000.0;
+            Line::from(vec![
+                Span::styled(
+                    format!(" {:<width$}  ", breach.iface, width = name_width),
+                    bold_title_style(colors.alert),
+                ),
+                Span::raw(rule.to_string()),
+                Span::styled(
+                    format!(
+                        "   now {}",
+                        format_metric(rule.metric, breach.value, app.display.show_bits)
+                    ),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!("   for {}", format_span(held))),
+            ])
+        })
+        .collect::<Vec<_>>();
+    f.render_widget(
+        Paragraph::new(lines).block(block).style(app.block_style()),
+        area,
+    );
+}
+
+fn render_events(f: &mut Frame<'_>, area: ratatui::layout::Rect, app: &App) {
+    let colors = app.colors();
+    let right = if app.event_scroll > 0 {
+        format!("↑ {} older  pgup/pgdn", app.event_scroll)
+    } else {
+        "pgup/pgdn".to_string()
+    };
+    let block = bordered_block(
+        colors.pane,
+        BorderType::Thick,
+        Some(title_line_bold(
+            "Events",
+            &format!(" ({})", app.events.len()),
+            colors.pane,
+        )),
+        Some(title_line(&right).alignment(Alignment::Right)),
+        app.block_style(),
+    );
+    let rows = block.inner(area).height as usize;
+    let name_width = app
+        .events
+        .iter()
+        .filter_map(|e| e.iface.as_deref())
+        .map(UnicodeWidthStr::width)
+        .max()
+        .unwrap_or(0);
+    let lines = app
+        .events
+        .iter()
+        .skip(app.event_scroll)
+        .take(rows)
+        .map(|event| {
+            Line::from(vec![
+                Span::styled(
+                    format!(" {} ", event.clock()),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!(
+                        "{:<width$}  ",
+                        event.iface.as_deref().unwrap_or(""),
+                        width = name_width
+                    ),
+                    bold_title_style(colors.tile),
+                ),
+                Span::styled(event.message.clone(), event_style(event.kind, &colors)),
+            ])
+        })
+        .collect::<Vec<_>>();
+    f.render_widget(
+        Paragraph::new(lines).block(block).style(app.block_style()),
+        area,
+    );
+}
+
+fn format_clock(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+fn key_hint(app: &App, key: &str, label: &str) -> Vec<Span<'static>> {
+    vec![
+        Span::styled(key.to_string(), key_style(app)),
+        Span::raw(format!(" {label}")),
+    ]
+}
+
+fn replay_status(app: &mut App) -> Option<Line<'static>> {
+    let (position, duration, speed, state) = {
+        let replay = app.sampler.replay()?;
+        let state = if replay.finished() {
+            "end"
+        } else if replay.paused {
+            "paused"
+        } else {
+            "playing"
+        };
+        (replay.position(), replay.duration(), replay.speed, state)
+    };
+    let mut spans = vec![Span::raw(format!(
+        " replay {}/{} x{} {}",
+        format_clock(position),
+        format_clock(duration),
+        speed,
+        state
+    ))];
+    append_sep(&mut spans);
+    spans.extend(key_hint(app, "space", "pause"));
+    append_sep(&mut spans);
+    spans.extend(key_hint(app, "[ ]", "seek"));
+    append_sep(&mut spans);
+    spans.extend(key_hint(app, "< >", "speed"));
+    if app.scrollback.is_some() {
+        append_sep(&mut spans);
+        spans.extend(key_hint(app, "← →", "scroll"));
+    }
+    spans.push(Span::raw(" "));
+    Some(Line::from(spans))
+}
+
+fn pause_status(app: &App) -> Option<Line<'static>> {
+    app.scrollback.as_ref()?;
+    let mut spans = vec![Span::styled(
+        " paused",
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    append_sep(&mut spans);
+    spans.extend(key_hint(app, "space", "resume"));
+    append_sep(&mut spans);
+    spans.extend(key_hint(app, "← →", "scroll"));
+    append_sep(&mut spans);
+    spans.extend(key_hint(app, "shift", "×10"));
+    spans.push(Span::raw(" "));
+    Some(Line::from(spans))
 }
 
 pub fn ui(f: &mut Frame<'_>, app: &mut App) {
@@ -597,6 +1576,20 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
         },
     ));
     append_sep(&mut footer);
+    footer.extend(cmd_bold_prefix(
+        app,
+        if app.display.link_scale {
+            "util scale: link"
+        } else {
+            "util scale: auto"
+        },
+    ));
+    append_sep(&mut footer);
+    footer.extend(cmd_bold_prefix(
+        app,
+        &format!("window: {}", app.display.window.label()),
+    ));
+    append_sep(&mut footer);
     footer.extend(cmd_bold_prefix(
         app,
         if app.display.show_bits {
@@ -653,7 +1646,7 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
     footer.extend(cmd_bold_prefix(
         app,
         &format!(
-            "loopback {}",
+            "Loopback {}",
             if app.display.show_loopback {
                 "●"
             } else {
@@ -661,10 +1654,63 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
             }
         ),
     ));
+    if !app.hidden.is_empty() {
+        append_sep(&mut footer);
+        footer.extend(cmd_bold_prefix(
+            app,
+            &format!(
+                "Hidden {}",
+                if app.display.show_hidden {
+                    "●"
+                } else {
+                    "○"
+                }
+            ),
+        ));
+    }
+    append_sep(&mut footer);
+    footer.extend(cmd_bold_prefix(
+        app,
+        &format!(
+            "events {}",
+            if app.display.show_events {
+                "●"
+            } else {
+                "○"
+            }
+        ),
+    ));
+    append_sep(&mut footer);
+    footer.extend(cmd_bold_prefix(
+        app,
+        &format!(
+            "data usage {}",
+            if app.display.show_usage { "●" } else { "○" }
+        ),
+    ));
     append_sep(&mut footer);
     footer.extend(cmd_bold_prefix(app, "refresh names"));
     append_sep(&mut footer);
-    footer.extend(cmd_bold_prefix(app, &format!("+/- {}ms", app.interval_ms)));
+    if app.detail.is_some() {
+        footer.extend(key_hint(app, "esc", "back"));
+        append_sep(&mut footer);
+        footer.extend(key_hint(app, "tab", "next"));
+    } else {
+        footer.extend(key_hint(app, "enter", "details"));
+    }
+    // Both act on the focused tile, so name what they'd do to it.
+    let focus = app.focus.as_deref();
+    let pinned = focus.is_some_and(|name| app.is_pinned(name));
+    let hidden = focus.is_some_and(|name| matches_any(&app.hidden, name));
+    append_sep(&mut footer);
+    footer.extend(key_hint(app, "p", if pinned { "unpin" } else { "pin" }));
+    append_sep(&mut footer);
+    footer.extend(key_hint(app, "x", if hidden { "unhide" } else { "hide" }));
+    append_sep(&mut footer);
+    let replay_line = replay_status(app);
+    if replay_line.is_none() {
+        footer.extend(cmd_bold_prefix(app, &format!("+/- {}ms", app.interval_ms)));
+    }
 
     if let Some(err) = &app.last_error {
         append_sep(&mut footer);
@@ -692,9 +1738,58 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
         app.block_style(),
     )
     .title_bottom(Line::from(footer).alignment(Alignment::Right));
+    let outer = match replay_line.or_else(|| pause_status(app)) {
+        Some(line) => outer.title_top(line.alignment(Alignment::Center)),
+        None => outer,
+    };
     let inner = outer.inner(f.area());
     f.render_widget(outer, f.area());
 
+    let active_alerts = app.alerts.active().len();
+    let inner = if active_alerts > 0 {
+        let height = active_alerts.min(ALERT_PANE_ROWS) as u16 + 2;
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(height), Constraint::Min(0)])
+            .split(inner);
+        render_alerts(f, parts[0], app);
+        parts[1]
+    } else {
+        inner
+    };
+
+    let inner = if app.display.show_events {
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(EVENT_PANE_HEIGHT)])
+            .split(inner);
+        render_events(f, parts[1], app);
+        parts[0]
+    } else {
+        inner
+    };
+
+    let inner = if app.display.show_usage {
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(usage_pane_height(app)),
+            ])
+            .split(inner);
+        render_usage(f, parts[1], app);
+        parts[0]
+    } else {
+        inner
+    };
+
+    if let Some(iface) = app.detail.as_ref().and_then(|name| app.states.get(name)) {
+        render_detail(f, inner, app, iface);
+        return;
+    }
+    app.detail = None;
+    app.tiles.clear();
+
     let mut section_count = 1; // physical is always shown
     if app.display.show_overview {
         section_count += 1;
@@ -731,3 +1826,17 @@ pub fn ui(f: &mut Frame<'_>, app: &mut App) {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn link_speeds_use_decimal_units() {
+        assert_eq!(format_link_speed(0), "0 b/s");
+        assert_eq!(format_link_speed(10_000_000), "10 Mb/s");
+        assert_eq!(format_link_speed(1_000_000_000), "1 Gb/s");
+        assert_eq!(format_link_speed(2_500_000_000), "2.5 Gb/s");
+        assert_eq!(format_link_speed(866_700_000), "866.7 Mb/s");
+    }
+}
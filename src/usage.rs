@@ -0,0 +1,354 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::config_path;
+use crate::constants::{QUOTA_WARN_LEVELS, USAGE_SAVE_SECS};
+use crate::events::{local_time, EventKind, EventLog};
+
+const STATE_FILE: &str = "macnetmon-usage.json";
+
+/// Bytes moved in each direction over some period.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Totals {
+    pub rx: u64,
+    pub tx: u64,
+}
+
+impl Totals {
+    pub fn total(&self) -> u64 {
+        self.rx.saturating_add(self.tx)
+    }
+
+    fn add(&mut self, rx: u64, tx: u64) {
+        self.rx = self.rx.saturating_add(rx);
+        self.tx = self.tx.saturating_add(tx);
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct IfaceUsage {
+    /// Local date `today` belongs to, as `YYYY-MM-DD`.
+    day: String,
+    today: Totals,
+    /// First day of the billing month `month` belongs to.
+    period: String,
+    month: Totals,
+    /// Highest quota warning already given this billing month.
+    warned: u8,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct State {
+    interfaces: BTreeMap<String, IfaceUsage>,
+}
+
+/// A data allowance as configured, e.g. `20 GB`.
+#[derive(Clone, Debug)]
+pub struct Quota {
+    pub bytes: u64,
+    pub text: String,
+}
+
+impl Quota {
+    /// Parses a size like `500 MB`, `20GB` or `1.5 TB` (1024 steps).
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let compact: String = text.split_whitespace().collect();
+        let split = compact
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(compact.len());
+        let (number, unit) = compact.split_at(split);
+        let value: f64 = number
+            .parse()
+            .map_err(|_| format!("\"{text}\" is not a size"))?;
+        let scale = match unit.to_ascii_uppercase().as_str() {
+            "" | "B" => 1u64,
+            "K" | "KB" => 1 << 10,
+            "M" | "MB" => 1 << 20,
+            "G" | "GB" => 1 << 30,
+            "T" | "TB" => 1 << 40,
+            _ => return Err(format!("unknown size unit \"{unit}\"")),
+        };
+        let bytes = (value * scale as f64) as u64;
+        if bytes == 0 {
+            return Err("quota must be more than 0 bytes".to_string());
+        }
+        Ok(Self {
+            bytes,
+            text: text.trim().to_string(),
+        })
+    }
+}
+
+/// Where the current day and billing month start, in local time.
+#[derive(Clone, Default)]
+struct Dates {
+    day: String,
+    period: String,
+}
+
+impl Dates {
+    fn now(billing_day: u32) -> Self {
+        let Some(tm) = local_time(SystemTime::now()) else {
+            return Self::default();
+        };
+        Self::for_date(
+            tm.tm_year + 1900,
+            tm.tm_mon as u32 + 1,
+            tm.tm_mday as u32,
+            billing_day,
+        )
+    }
+
+    /// Dates for the local calendar day `year`-`month`-`day`, before the
+    /// billing day rolls the period back into the previous month.
+    fn for_date(mut year: i32, mut month: u32, day: u32, billing_day: u32) -> Self {
+        let today = format!("{year:04}-{month:02}-{day:02}");
+        if day < billing_day {
+            month -= 1;
+            if month == 0 {
+                month = 12;
+                year -= 1;
+            }
+        }
+        Self {
+            day: today,
+            period: format!("{year:04}-{month:02}-{billing_day:02}"),
+        }
+    }
+}
+
+/// Cumulative traffic per interface for today and the current billing month,
+/// kept in a state file so it survives restarts. Only traffic seen while
+/// macnetmon is running is counted.
+pub struct Usage {
+    state: State,
+    path: Option<PathBuf>,
+    billing_day: u32,
+    pub quota: Option<Quota>,
+    dates: Dates,
+    dirty: bool,
+    last_save: Instant,
+}
+
+impl Usage {
+    pub fn load(billing_day: u32, quota: Option<Quota>) -> Self {
+        let path = config_path(STATE_FILE);
+        let state = path
+            .as_ref()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self {
+            state,
+            path,
+            billing_day,
+            quota,
+            dates: Dates::now(billing_day),
+            dirty: false,
+            last_save: Instant::now(),
+        }
+    }
+
+    /// Adds one tick's byte deltas per interface, warning through `events`
+    /// as an interface crosses each quota level. Returns whether a warning
+    /// was given.
+    pub fn record(&mut self, deltas: &[(String, u64, u64)], events: &mut EventLog) -> bool {
+        self.dates = Dates::now(self.billing_day);
+        let mut warned = false;
+        for (name, rx, tx) in deltas {
+            if *rx == 0 && *tx == 0 && !self.state.interfaces.contains_key(name) {
+                continue;
+            }
+            let usage = self.state.interfaces.entry(name.clone()).or_default();
+            if usage.day != self.dates.day {
+                usage.day = self.dates.day.clone();
+                usage.today = Totals::default();
+            }
+            if usage.period != self.dates.period {
+                usage.period = self.dates.period.clone();
+                usage.month = Totals::default();
+                usage.warned = 0;
+            }
+            usage.today.add(*rx, *tx);
+            usage.month.add(*rx, *tx);
+            self.dirty |= *rx > 0 || *tx > 0;
+
+            let Some(quota) = &self.quota else {
+                continue;
+            };
+            let percent = usage.month.total() as f64 / quota.bytes as f64 * 100.0;
+            let Some(level) = QUOTA_WARN_LEVELS
+                .iter()
+                .copied()
+                .filter(|level| percent >= *level as f64 && *level > usage.warned)
+                .max()
+            else {
+                continue;
+            };
+            usage.warned = level;
+            self.dirty = true;
+            warned = true;
+            let message = if level >= 100 {
+                format!("monthly quota of {} used up", quota.text)
+            } else {
+                format!("{level}% of the {} monthly quota used", quota.text)
+            };
+            events.push(EventKind::Alert, Some(name), message);
+        }
+
+        if self.last_save.elapsed() >= Duration::from_secs(USAGE_SAVE_SECS) {
+            self.save();
+        }
+        warned
+    }
+
+    /// Totals for `name` today, or zero if nothing has been counted today.
+    pub fn today(&self, name: &str) -> Totals {
+        self.state
+            .interfaces
+            .get(name)
+            .filter(|usage| usage.day == self.dates.day)
+            .map(|usage| usage.today)
+            .unwrap_or_default()
+    }
+
+    /// Totals for `name` in the current billing month.
+    pub fn month(&self, name: &str) -> Totals {
+        self.state
+            .interfaces
+            .get(name)
+            .filter(|usage| usage.period == self.dates.period)
+            .map(|usage| usage.month)
+            .unwrap_or_default()
+    }
+
+    /// Share of the quota `name` has used this billing month.
+    pub fn quota_percent(&self, name: &str) -> Option<f64> {
+        let quota = self.quota.as_ref()?;
+        Some(self.month(name).total() as f64 / quota.bytes as f64 * 100.0)
+    }
+
+    /// First day of the current billing month, `YYYY-MM-DD`.
+    pub fn period(&self) -> &str {
+        &self.dates.period
+    }
+
+    /// Writes the state file if anything changed since the last save. The
+    /// file is replaced atomically so a crash mid-write can't lose totals.
+    pub fn save(&mut self) {
+        self.last_save = Instant::now();
+        if !self.dirty {
+            return;
+        }
+        let Some(path) = &self.path else {
+            return;
+        };
+        let Ok(json) = serde_json::to_vec_pretty(&self.state) else {
+            return;
+        };
+        let tmp = path.with_extension("json.tmp");
+        if fs::write(&tmp, json).is_ok() && fs::rename(&tmp, path).is_ok() {
+            self.dirty = false;
+        }
+    }
+}
+
+impl Drop for Usage {
+    fn drop(&mut self) {
+        self.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(quota: &str) -> Usage {
+        Usage {
+            state: State::default(),
+            path: None,
+            billing_day: 1,
+            quota: Some(Quota::parse(quota).unwrap()),
+            dates: Dates::default(),
+            dirty: false,
+            last_save: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn quotas_take_units() {
+        for (text, bytes) in [
+            ("500", 500),
+            ("500 B", 500),
+            ("2k", 2 << 10),
+            ("500 MB", 500 << 20),
+            ("20GB", 20 << 30),
+            ("1.5 tb", 3 << 39),
+            (" 1 G ", 1 << 30),
+        ] {
+            assert_eq!(
+                Quota::parse(text).map(|quota| quota.bytes),
+                Ok(bytes),
+                "{text:?}"
+            );
+        }
+        assert_eq!(Quota::parse(" 20 GB ").unwrap().text, "20 GB");
+        for text in [
+            "", "GB", "20 PB", "20 GiB", "0 MB", "0.1 B", "-5 GB", "1.2.3 GB",
+        ] {
+            assert!(Quota::parse(text).is_err(), "accepted {text:?}");
+        }
+    }
+
+    #[test]
+    fn billing_periods_start_on_the_billing_day() {
+        let dates = Dates::for_date(2026, 3, 20, 15);
+        assert_eq!(
+            (dates.day.as_str(), dates.period.as_str()),
+            ("2026-03-20", "2026-03-15")
+        );
+        assert_eq!(Dates::for_date(2026, 3, 15, 15).period, "2026-03-15");
+        assert_eq!(Dates::for_date(2026, 3, 14, 15).period, "2026-02-15");
+        assert_eq!(Dates::for_date(2026, 3, 1, 1).period, "2026-03-01");
+
+        // Before the billing day in January, the period began last December.
+        let dates = Dates::for_date(2026, 1, 5, 15);
+        assert_eq!(
+            (dates.day.as_str(), dates.period.as_str()),
+            ("2026-01-05", "2025-12-15")
+        );
+        assert_eq!(Dates::for_date(2026, 1, 15, 15).period, "2026-01-15");
+    }
+
+    #[test]
+    fn each_quota_level_warns_once() {
+        let mut usage = usage("1000");
+        let mut events = EventLog::default();
+        let mut warnings = 0;
+        for _ in 0..15 {
+            warnings += usage.record(&[("en0".to_string(), 60, 40)], &mut events) as usize;
+        }
+        let messages: Vec<&str> = events.iter().map(|event| event.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "monthly quota of 1000 used up",
+                "80% of the 1000 monthly quota used"
+            ]
+        );
+        assert_eq!(warnings, QUOTA_WARN_LEVELS.len());
+        assert_eq!(usage.month("en0").total(), 1500);
+
+        // Jumping past several levels at once only gives the highest.
+        let mut events = EventLog::default();
+        usage.record(&[("en1".to_string(), 2000, 0)], &mut events);
+        usage.record(&[("en1".to_string(), 10, 0)], &mut events);
+        assert_eq!(events.len(), 1);
+    }
+}